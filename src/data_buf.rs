@@ -1,6 +1,7 @@
 //
 // Implementation of the `DataBuf` trait
 //
+use core::mem;
 use core::mem::MaybeUninit;
 
 /// Trait used to represent a data buffer, typically you'll passs a `[usize; N]` array.
@@ -24,6 +25,30 @@ pub unsafe trait DataBuf {
     /// Extend the buffer (fallible)
     fn extend(&mut self, len: usize) -> Result<(), ()>;
 
+    /// Extend the buffer to exactly `len` words, without over-allocating (fallible)
+    ///
+    /// Defaults to `extend`, for buffers that can't over-allocate
+    fn extend_exact(&mut self, len: usize) -> Result<(), ()> {
+        self.extend(len)
+    }
+
+    /// Called by the collections after the buffer has grown from `old` to `new` words
+    ///
+    /// Can be used for instrumentation (see `buffers::Observed`)
+    fn on_extend(&mut self, _old: usize, _new: usize) {}
+
+    /// Maximum number of words this buffer type can ever hold (`usize::MAX` if unbounded)
+    fn max_words() -> usize {
+        usize::MAX
+    }
+
+    /// Alignment of the start of the data (only used by `Value`, which stores its data there)
+    ///
+    /// Must hold for the current location of `self`, and for wherever it is moved to.
+    fn data_alignment(&self) -> usize {
+        mem::align_of::<Self::Inner>()
+    }
+
     /// Convert a byte count to a word count (rounding up)
     fn round_to_words(bytes: usize) -> usize {
         crate::round_to_words::<Self::Inner>(bytes)
@@ -41,6 +66,7 @@ macro_rules! impl_pod {
     }
 }
 impl_pod! { u8, u16, u32, u64, u128, usize }
+impl_pod! { i8, i16, i32, i64, i128, isize }
 
 unsafe impl<T, U> DataBuf for &mut T
 where
@@ -57,6 +83,18 @@ where
     fn extend(&mut self, len: usize) -> Result<(), ()> {
         (**self).extend(len)
     }
+    fn extend_exact(&mut self, len: usize) -> Result<(), ()> {
+        (**self).extend_exact(len)
+    }
+    fn on_extend(&mut self, old: usize, new: usize) {
+        (**self).on_extend(old, new)
+    }
+    fn max_words() -> usize {
+        T::max_words()
+    }
+    fn data_alignment(&self) -> usize {
+        (**self).data_alignment()
+    }
 }
 
 #[cfg(not(feature = "const_generics"))]
@@ -78,9 +116,13 @@ macro_rules! impl_databuf_array {
                     Ok( () )
                 }
             }
+            fn max_words() -> usize {
+                $n
+            }
         })*
     }
 }
+// Without `const_generics` only a selection of sizes are supported (`buffers::Array` supports any size)
 #[cfg(not(feature = "const_generics"))]
 impl_databuf_array! {
      0, 1, 2, 3, 4, 5, 6, 7, 8, 9,
@@ -93,6 +135,8 @@ impl_databuf_array! {
     256
 }
 /// Array-specific impl
+///
+/// Without the `const_generics` feature, only some sizes are supported. Prefer `buffers::Array`.
 #[cfg(feature = "const_generics")]
 unsafe impl<T: Pod, const N: usize> DataBuf for [MaybeUninit<T>; N] {
     type Inner = T;
@@ -109,6 +153,9 @@ unsafe impl<T: Pod, const N: usize> DataBuf for [MaybeUninit<T>; N] {
             Ok(())
         }
     }
+    fn max_words() -> usize {
+        N
+    }
 }
 
 /// Vector backed structures, can be used to auto-grow the allocation
@@ -139,4 +186,12 @@ unsafe impl<T: Pod> crate::DataBuf for ::alloc::vec::Vec<MaybeUninit<T>> {
         }
         Ok(())
     }
+    fn extend_exact(&mut self, len: usize) -> Result<(), ()> {
+        if len > self.len() {
+            let extra = len - self.len();
+            self.reserve_exact(extra);
+            self.resize(len, MaybeUninit::uninit());
+        }
+        Ok(())
+    }
 }
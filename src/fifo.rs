@@ -18,9 +18,14 @@ pub struct Fifo<T: ?Sized, D: ::DataBuf> {
     _pd: marker::PhantomData<*const T>,
     read_pos: usize,
     write_pos: usize,
+    // Number of items currently in the queue
+    count: usize,
     data: D,
 }
 impl<T: ?Sized, D: ::DataBuf> Fifo<T, D> {
+    /// Size of this type in bytes (i.e. `mem::size_of::<Self>()`), for checking stack usage
+    pub const FOOTPRINT: usize = mem::size_of::<Self>();
+
     /// Construct a new (empty) list
     pub fn new() -> Self
     where
@@ -34,6 +39,7 @@ impl<T: ?Sized, D: ::DataBuf> Fifo<T, D> {
             _pd: marker::PhantomData,
             read_pos: 0,
             write_pos: 0,
+            count: 0,
             data,
         }
     }
@@ -44,6 +50,118 @@ impl<T: ?Sized, D: ::DataBuf> Fifo<T, D> {
     fn space_words(&self) -> usize {
         self.data.as_ref().len() - self.write_pos
     }
+    /// Check the internal offsets are consistent (compiled out in release builds)
+    #[inline]
+    fn check_invariants(&self) {
+        debug_assert!(
+            self.read_pos <= self.write_pos && self.write_pos <= self.data.as_ref().len(),
+            "Fifo invariant violated: read_pos={} write_pos={} buffer_len={}",
+            self.read_pos,
+            self.write_pos,
+            self.data.as_ref().len()
+        );
+    }
+
+    /// Returns the size of the buffer, in words (of `D::Inner`)
+    pub fn capacity(&self) -> usize {
+        self.check_invariants();
+        self.data.as_ref().len()
+    }
+    /// Returns the size of the buffer, in bytes
+    pub fn capacity_bytes(&self) -> usize {
+        self.check_invariants();
+        self.data.as_ref().len() * mem::size_of::<D::Inner>()
+    }
+    /// Returns the number of bytes used by per-item metadata (e.g. vtable pointers or lengths) for
+    /// the current items
+    ///
+    /// ```
+    /// let mut list = ::stack_dst::Fifo::<str, ::stack_dst::buffers::Ptr8>::new();
+    /// list.push_back_str("Hello");
+    /// list.push_back_str("World");
+    /// assert_eq!(list.overhead_bytes(), 2 * std::mem::size_of::<usize>());
+    /// ```
+    pub fn overhead_bytes(&self) -> usize {
+        self.check_invariants();
+        self.count * Self::meta_words() * mem::size_of::<D::Inner>()
+    }
+
+    /// Returns the number of `U` values that can be pushed without growing the buffer
+    ///
+    /// ```
+    /// # use std::any::Any;
+    /// let mut list = ::stack_dst::Fifo::<dyn Any, ::stack_dst::buffers::Ptr8>::new();
+    /// // Each item takes one word for the value, and one for the vtable
+    /// assert_eq!(list.remaining_capacity_for::<usize>(), 4);
+    /// ```
+    pub fn remaining_capacity_for<U>(&self) -> usize {
+        self.check_invariants();
+        let words = Self::meta_words() + D::round_to_words(mem::size_of::<U>());
+        if words == 0 {
+            return usize::MAX;
+        }
+        // Space before `read_pos` is reclaimed by compacting when pushing
+        (self.space_words() + self.read_pos) / words
+    }
+
+    /// Ensure that at least `words` words (of `D::Inner`) are free, growing the buffer if needed
+    ///
+    /// The buffer may over-allocate (see `DataBuf::extend`), use `reserve_exact` to avoid this.
+    /// Fixed-size buffers can't grow, so this only succeeds if there is already enough space.
+    ///
+    /// ```
+    /// # #[cfg(feature = "alloc")] {
+    /// let mut list = ::stack_dst::Fifo::<str, ::stack_dst::buffers::PtrVec>::new();
+    /// list.reserve(10).unwrap();
+    /// assert!(list.capacity() >= 10);
+    /// # }
+    /// ```
+    pub fn reserve(&mut self, words: usize) -> Result<(), ()> {
+        self.check_invariants();
+        self.grow(words, false)
+    }
+
+    /// Ensure that at least `words` words (of `D::Inner`) are free, without over-allocating
+    ///
+    /// ```
+    /// # #[cfg(feature = "alloc")] {
+    /// let mut list = ::stack_dst::Fifo::<str, ::stack_dst::buffers::PtrVec>::new();
+    /// list.reserve_exact(10).unwrap();
+    /// assert_eq!(list.capacity(), 10);
+    /// # }
+    /// ```
+    pub fn reserve_exact(&mut self, words: usize) -> Result<(), ()> {
+        self.check_invariants();
+        self.grow(words, true)
+    }
+
+    /// Ensure that `n` more items of type `U` can be pushed without growing the buffer again
+    ///
+    /// Each item needs `round_to_words(size_of::<U>())` words for the data, plus the metadata.
+    ///
+    /// ```
+    /// # #[cfg(feature = "alloc")] {
+    /// # use std::any::Any;
+    /// let mut list = ::stack_dst::Fifo::<dyn Any, ::stack_dst::buffers::PtrVec>::new();
+    /// list.reserve_items::<u64>(10).unwrap();
+    /// assert!(list.remaining_capacity_for::<u64>() >= 10);
+    /// # }
+    /// ```
+    pub fn reserve_items<U>(&mut self, n: usize) -> Result<(), ()> {
+        self.check_invariants();
+        let words = Self::meta_words() + D::round_to_words(mem::size_of::<U>());
+        let total = words.checked_mul(n).ok_or(())?;
+        self.grow(total, false)
+    }
+
+    fn grow(&mut self, words: usize, exact: bool) -> Result<(), ()> {
+        if self.space_words() + self.read_pos < words {
+            self.compact_inner();
+            let req_space = self.write_pos.checked_add(words).ok_or(())?;
+            crate::extend_buf(&mut self.data, exact, req_space)?;
+        }
+        Ok(())
+    }
 
     /// Push a value at the top of the stack
     #[cfg(feature = "unsize")]
@@ -59,22 +177,126 @@ impl<T: ?Sized, D: ::DataBuf> Fifo<T, D> {
     where
         (U, D::Inner): crate::AlignmentValid,
     {
+        self.check_invariants();
         <(U, D::Inner) as crate::AlignmentValid>::check();
+        self.try_push_back_stable_inner(v, f)
+            .map_err(|e| e.into_inner())
+    }
+
+    /// Push a value to the end of the list, reporting why the push failed
+    ///
+    /// Unlike `push_back_stable`, this returns `PushError::Misaligned` instead of panicking if `U`
+    /// is more strictly aligned than the buffer.
+    ///
+    /// ```
+    /// # use stack_dst::{Fifo, PushError};
+    /// let mut queue = Fifo::<[u8], ::stack_dst::buffers::Ptr2>::new();
+    /// assert_eq!(queue.try_push_back_stable([0u8; 64], |v| v).unwrap_err().kind(), PushError::TooLarge);
+    /// queue.try_push_back_stable([1], |v| v).unwrap();
+    /// assert_eq!(queue.try_push_back_stable([2], |v| v).unwrap_err().kind(), PushError::Full);
+    /// ```
+    pub fn try_push_back_stable<U, F: FnOnce(&U) -> &T>(
+        &mut self,
+        v: U,
+        f: F,
+    ) -> Result<(), crate::CapacityError<U>>
+    where
+        (U, D::Inner): crate::AlignmentValid,
+    {
+        self.check_invariants();
+        self.try_push_back_stable_inner(v, f)
+    }
+    fn try_push_back_stable_inner<U, F: FnOnce(&U) -> &T>(
+        &mut self,
+        v: U,
+        f: F,
+    ) -> Result<(), crate::CapacityError<U>>
+    where
+        (U, D::Inner): crate::AlignmentValid,
+    {
+        if !crate::alignment_fits::<U, D::Inner>() {
+            return Err(crate::CapacityError::new(v, crate::PushError::Misaligned));
+        }
 
         // SAFE: Destination address is valid
         unsafe {
             match self.push_inner(crate::check_fat_pointer(&v, f)) {
                 Ok(pii) => {
                     ptr::write(pii.data.as_mut_ptr() as *mut U, v);
+                    self.count += 1;
                     Ok(())
                 }
-                Err(_) => Err(v),
+                Err(e) => Err(crate::CapacityError::new(v, e)),
             }
         }
     }
 
+    /// Push a value to the end of the list, removing items from the front to make space if needed
+    ///
+    /// This allows using a fixed-size buffer as a bounded queue (e.g. a log of recent events).
+    /// Returns `Ok(Some(()))` if items were evicted, `Ok(None)` if there was already space, and
+    /// `Err(v)` if the value can't be stored even in an empty list.
+    ///
+    /// ```
+    /// let mut queue = ::stack_dst::Fifo::<[u8], ::stack_dst::buffers::Ptr8>::new();
+    /// assert_eq!(queue.push_back_evicting([1u8; 16], |v| v), Ok(None));
+    /// assert_eq!(queue.push_back_evicting([2u8; 16], |v| v), Ok(None));
+    /// assert_eq!(queue.push_back_evicting([3u8; 16], |v| v), Ok(Some(())));
+    /// assert_eq!(queue.front(), Some(&[2u8; 16][..]));
+    /// ```
+    pub fn push_back_evicting<U, F: FnOnce(&U) -> &T>(
+        &mut self,
+        v: U,
+        f: F,
+    ) -> Result<Option<()>, U>
+    where
+        (U, D::Inner): crate::AlignmentValid,
+    {
+        self.check_invariants();
+        <(U, D::Inner) as crate::AlignmentValid>::check();
+
+        let mut evicted = false;
+        // SAFE: Destination address is valid
+        unsafe {
+            let fat_ptr = crate::check_fat_pointer(&v, f);
+            loop {
+                let e = match self.push_inner(fat_ptr) {
+                    Ok(pii) => {
+                        ptr::write(pii.data.as_mut_ptr() as *mut U, v);
+                        self.count += 1;
+                        return Ok(if evicted { Some(()) } else { None });
+                    }
+                    Err(e) => e,
+                };
+                if !self.evict_for_retry(e) {
+                    return Err(v);
+                }
+                evicted = true;
+            }
+        }
+    }
+
+    /// Handle a failed push for the `*_evicting` methods, dropping the front item if that could help
+    ///
+    /// Returns `true` if an item was dropped (and the push should be retried)
+    fn evict_for_retry(&mut self, e: crate::PushError) -> bool {
+        match e {
+            crate::PushError::Full | crate::PushError::AllocFailed
+                if self.read_pos != self.write_pos =>
+            {
+                self.pop_front_inner();
+                true
+            }
+            _ => false,
+        }
+    }
+
     /// Compact the list (moving the read position to zero)
     pub fn compact(&mut self) {
+        self.check_invariants();
+        self.compact_inner()
+    }
+    fn compact_inner(&mut self) {
         if self.read_pos != 0 {
             self.data.as_mut().rotate_left(self.read_pos);
             self.write_pos -= self.read_pos;
@@ -83,28 +305,143 @@ impl<T: ?Sized, D: ::DataBuf> Fifo<T, D> {
     }
 
     /// Checks if the queue is currently empty
-    pub fn empty(&self) -> bool {
+    ///
+    /// ```
+    /// let mut list = ::stack_dst::Fifo::<str, ::stack_dst::buffers::Ptr8>::new();
+    /// assert!(list.is_empty());
+    /// list.push_back_str("Hello");
+    /// assert!(!list.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.check_invariants();
         self.read_pos == self.write_pos
     }
+    /// Checks if the queue is currently empty
+    #[deprecated(note = "Use `is_empty` instead")]
+    pub fn empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    /// Returns the number of items in the queue
+    ///
+    /// ```
+    /// let mut list = ::stack_dst::Fifo::<str, ::stack_dst::buffers::Ptr8>::new();
+    /// list.push_back_str("Hello");
+    /// list.push_back_str("world");
+    /// assert_eq!(list.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.check_invariants();
+        self.count
+    }
+
+    /// Convert a list holding exactly one item into a `Value`, reusing the buffer
+    ///
+    /// Returns the list unchanged if it doesn't hold exactly one item.
+    ///
+    /// ```
+    /// let mut list = ::stack_dst::Fifo::<str, ::stack_dst::buffers::Ptr8>::new();
+    /// list.push_back_str("Hello").unwrap();
+    /// let v = list.into_single().ok().unwrap();
+    /// assert_eq!(&v[..], "Hello");
+    /// ```
+    pub fn into_single(self) -> Result<::Value<T, D>, Self> {
+        self.check_invariants();
+        if self.count != 1 {
+            return Err(self);
+        }
+        let mut this = mem::ManuallyDrop::new(self);
+        // SAFE: The list holds an item at `read_pos`, and the list is forgotten (without dropping
+        // the item) once its buffer is taken
+        unsafe {
+            let item = this.front_raw_mut();
+            Ok(::Value::take_from_buffer(&mut this.data, item))
+        }
+    }
 
     /// Remove an item from the front of the list
-    pub fn pop_front(&mut self) -> Option<PopHandle<T, D>> {
+    ///
+    /// In debug builds, the freed words are overwritten with `0xDE` bytes once the item is dropped.
+    pub fn pop_front(&mut self) -> Option<PopHandle<'_, T, D>> {
+        self.check_invariants();
         if self.read_pos == self.write_pos {
             None
         } else {
             Some(PopHandle { parent: self })
         }
     }
+    /// Remove all items (dropping them), and rewind to the start of the buffer
+    ///
+    /// ```
+    /// let mut queue = ::stack_dst::Fifo::<str, ::stack_dst::buffers::Ptr8>::new();
+    /// queue.push_back_str("Hello");
+    /// queue.clear();
+    /// assert!(queue.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.check_invariants();
+        while self.read_pos != self.write_pos {
+            self.pop_front_inner();
+        }
+        self.read_pos = 0;
+        self.write_pos = 0;
+    }
+    /// Remove up to `n` items from the front of the list, returning the number removed
+    ///
+    /// ```
+    /// let mut list = ::stack_dst::Fifo::<str, ::stack_dst::buffers::Ptr8>::new();
+    /// list.push_back_str("a").unwrap();
+    /// list.push_back_str("b").unwrap();
+    /// list.push_back_str("c").unwrap();
+    /// assert_eq!(list.pop_front_n(2), 2);
+    /// assert_eq!(list.front(), Some("c"));
+    /// assert_eq!(list.pop_front_n(5), 1);
+    /// ```
+    pub fn pop_front_n(&mut self, n: usize) -> usize {
+        self.check_invariants();
+        let n = ::core::cmp::min(n, self.count);
+        for _ in 0..n {
+            self.pop_front_inner();
+        }
+        n
+    }
     /// Peek the front of the queue
     pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.check_invariants();
         if self.read_pos == self.write_pos {
             None
         } else {
             Some(unsafe { &mut *self.front_raw_mut() })
         }
     }
+    /// Returns the front item, first pushing the result of `make` if the queue is empty
+    ///
+    /// Returns `Err` with the new value if it couldn't be pushed
+    ///
+    /// ```
+    /// # use std::fmt::Display;
+    /// let mut queue = ::stack_dst::Fifo::<dyn Display, ::stack_dst::buffers::Ptr8>::new();
+    /// assert_eq!(queue.front_or_push(|| 1, |v| v).ok().unwrap().to_string(), "1");
+    /// assert_eq!(queue.front_or_push(|| 2, |v| v).ok().unwrap().to_string(), "1");
+    /// ```
+    pub fn front_or_push<U, F, G>(&mut self, make: F, get_ref: G) -> Result<&mut T, U>
+    where
+        F: FnOnce() -> U,
+        G: FnOnce(&U) -> &T,
+        (U, D::Inner): crate::AlignmentValid,
+    {
+        self.check_invariants();
+        if self.read_pos == self.write_pos {
+            <(U, D::Inner) as crate::AlignmentValid>::check();
+            self.try_push_back_stable_inner(make(), get_ref)
+                .map_err(|e| e.into_inner())?;
+        }
+        // SAFE: The queue is non-empty, and the returned borrow is tied to `self`
+        Ok(unsafe { &mut *self.front_raw_mut() })
+    }
     /// Peek the front of the queue
     pub fn front(&self) -> Option<&T> {
+        self.check_invariants();
         if self.read_pos == self.write_pos {
             None
         } else {
@@ -122,8 +459,73 @@ impl<T: ?Sized, D: ::DataBuf> Fifo<T, D> {
     /// assert_eq!(it.next(), Some("world"));
     /// assert_eq!(it.next(), None);
     /// ```
-    pub fn iter(&self) -> Iter<T, D> {
-        Iter(self, self.read_pos)
+    pub fn iter(&self) -> Iter<'_, T, D> {
+        self.check_invariants();
+        Iter(self, self.read_pos, self.count)
+    }
+    /// Obtain an iterator that also yields the offset of each item, for use with `remove_at`
+    ///
+    /// Offsets are positions in the buffer, and are invalidated by any mutation of the queue.
+    ///
+    /// ```
+    /// let mut list = ::stack_dst::Fifo::<str, ::stack_dst::buffers::Ptr8>::new();
+    /// list.push_back_str("Hello");
+    /// list.push_back_str("cruel");
+    /// list.push_back_str("world");
+    /// let ofs = list.iter_with_offsets().find(|&(_, v)| v == "cruel").map(|(o, _)| o).unwrap();
+    /// assert!(list.remove_at(ofs));
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), ["Hello", "world"]);
+    /// ```
+    pub fn iter_with_offsets(&self) -> IterWithOffsets<'_, T, D> {
+        IterWithOffsets(self.iter())
+    }
+    /// Remove (and drop) the item at the given offset (from `iter_with_offsets`)
+    ///
+    /// Returns `false` if no item starts at `offset`.
+    pub fn remove_at(&mut self, offset: usize) -> bool {
+        self.check_invariants();
+        // Walk the queue to check that `offset` is the start of an item
+        let mut ofs = self.read_pos;
+        let mut n_before = 0;
+        while ofs < offset && ofs != self.write_pos {
+            // SAFE: Bounds checked, and the metadata is valid by internal consistency
+            let v = unsafe { &*self.raw_at(ofs) };
+            ofs += Self::meta_words() + D::round_to_words(mem::size_of_val(v));
+            n_before += 1;
+        }
+        if ofs != offset || ofs == self.write_pos {
+            return false;
+        }
+
+        // SAFE: `offset` is the start of an item (checked above)
+        let words = unsafe {
+            let v = &mut *self.raw_at_mut(offset);
+            let words = Self::meta_words() + D::round_to_words(mem::size_of_val(v));
+            /// Resets the count to just the items before the removed one (if the drop panics)
+            struct ResetCount<'a>(&'a mut usize, usize);
+            impl<'a> ops::Drop for ResetCount<'a> {
+                fn drop(&mut self) {
+                    *self.0 = self.1;
+                }
+            }
+            // Leak the following items if the drop panics
+            let orig_write_pos = mem::replace(&mut self.write_pos, offset);
+            let guard = ResetCount(&mut self.count, n_before);
+            ptr::drop_in_place(v);
+            mem::forget(guard);
+            self.count -= 1;
+            self.write_pos = orig_write_pos;
+            words
+        };
+        // Move the following items down over the removed item
+        let tail_len = self.write_pos - (offset + words);
+        // SAFE: Both ranges are within the buffer (`ptr::copy` handles the overlap)
+        unsafe {
+            let base = self.data.as_mut().as_mut_ptr();
+            ptr::copy(base.add(offset + words), base.add(offset), tail_len);
+        }
+        self.write_pos -= words;
+        true
     }
     /// Obtain a mutable iterator
     /// ```
@@ -138,8 +540,99 @@ impl<T: ?Sized, D: ::DataBuf> Fifo<T, D> {
     /// assert_eq!(it.next(), Some(&[8][..]));
     /// assert_eq!(it.next(), None);
     /// ```
-    pub fn iter_mut(&mut self) -> IterMut<T, D> {
-        IterMut(self, self.read_pos)
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, D> {
+        self.check_invariants();
+        IterMut(self, self.read_pos, self.count)
+    }
+
+    /// Returns the index (in iteration order, i.e. oldest first) of the first item matching `f`
+    ///
+    /// ```
+    /// let mut list = ::stack_dst::Fifo::<str, ::stack_dst::buffers::Ptr8>::new();
+    /// list.push_back_str("a");
+    /// list.push_back_str("bb");
+    /// assert_eq!(list.position(|v| v.len() == 2), Some(1));
+    /// assert_eq!(list.position(|v| v.is_empty()), None);
+    /// ```
+    pub fn position<F: FnMut(&T) -> bool>(&self, f: F) -> Option<usize> {
+        self.iter().position(f)
+    }
+    /// Returns the first item (in iteration order) matching `f`
+    pub fn find<F: FnMut(&T) -> bool>(&self, mut f: F) -> Option<&T> {
+        self.iter().find(|v| f(v))
+    }
+
+    /// Call `f` on each item (front first)
+    ///
+    /// Equivalent to `iter().for_each(f)`, but without the iterator's per-item state
+    /// ```
+    /// let mut list = ::stack_dst::Fifo::<dyn Fn() -> i32, ::stack_dst::buffers::Ptr8>::new();
+    /// list.push_back_stable(|| 1, |p| p).ok().unwrap();
+    /// list.push_back_stable(|| 2, |p| p).ok().unwrap();
+    /// let mut values = Vec::new();
+    /// list.for_each(|f| values.push(f()));
+    /// assert_eq!(values, [1, 2]);
+    /// ```
+    pub fn for_each<F: FnMut(&T)>(&self, mut f: F) {
+        self.check_invariants();
+        let mut ofs = self.read_pos;
+        while ofs != self.write_pos {
+            // SAFE: Bounds checked, and the metadata is valid by internal consistency
+            let v = unsafe { &*self.raw_at(ofs) };
+            ofs += Self::meta_words() + D::round_to_words(mem::size_of_val(v));
+            f(v);
+        }
+    }
+    /// Call `f` on each item (front first) with a mutable reference
+    pub fn for_each_mut<F: FnMut(&mut T)>(&mut self, mut f: F) {
+        self.check_invariants();
+        let mut ofs = self.read_pos;
+        while ofs != self.write_pos {
+            // SAFE: Bounds checked, and only one item is borrowed at a time
+            let v = unsafe { &mut *self.raw_at_mut(ofs) };
+            ofs += Self::meta_words() + D::round_to_words(mem::size_of_val(v));
+            f(v);
+        }
+    }
+
+    /// Move the first `count` items (or all items, if there are fewer) into a new queue
+    ///
+    /// The items are moved as raw words (not re-pushed), and keep their order. Returns `Err`
+    /// (leaving this queue unchanged) if the new buffer can't fit them.
+    ///
+    /// ```
+    /// let mut list = ::stack_dst::Fifo::<str, ::stack_dst::buffers::Ptr8>::new();
+    /// list.push_back_str("a");
+    /// list.push_back_str("b");
+    /// list.push_back_str("c");
+    /// let front = list.split_off_front(2).unwrap();
+    /// assert_eq!(front.iter().collect::<Vec<_>>(), ["a", "b"]);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), ["c"]);
+    /// ```
+    pub fn split_off_front(&mut self, count: usize) -> Result<Self, ()>
+    where
+        D: Default,
+    {
+        self.check_invariants();
+        let count = ::core::cmp::min(count, self.count);
+        let mut end = self.read_pos;
+        for _ in 0..count {
+            // SAFE: Bounds checked (by `raw_at` and `count`)
+            let v = unsafe { &*self.raw_at(end) };
+            end += Self::meta_words() + D::round_to_words(mem::size_of_val(v));
+        }
+        let words = end - self.read_pos;
+
+        let mut rv = Self::new();
+        crate::extend_buf(&mut rv.data, false, words)?;
+        rv.data.as_mut()[..words].copy_from_slice(&self.data.as_ref()[self.read_pos..end]);
+        rv.write_pos = words;
+        rv.count = count;
+
+        // The moved items are now owned by `rv`
+        self.read_pos = end;
+        self.count -= count;
+        Ok(rv)
     }
     // Note: No into_iter, not possible due to unsized types
     // Could make a `drain` that returns read handles (pops as it goes)
@@ -181,7 +674,10 @@ impl<T: ?Sized, D: ::DataBuf> Fifo<T, D> {
             let len = mem::size_of_val(ptr);
             ptr::drop_in_place(ptr);
             let words = D::round_to_words(len);
+            let old_pos = self.read_pos;
             self.read_pos += Self::meta_words() + words;
+            self.count -= 1;
+            crate::poison_words(&mut self.data.as_mut()[old_pos..self.read_pos]);
         }
     }
 
@@ -212,10 +708,14 @@ impl<T: ?Sized, D: ::DataBuf> Fifo<T, D> {
     where
         Cb: FnMut(&mut T) -> bool,
     {
+        self.check_invariants();
         let orig_write_pos = self.write_pos;
+        // Leak all items if `cb` panics
         self.write_pos = self.read_pos;
+        self.count = 0;
         let mut ofs = self.read_pos;
         let mut writeback_pos = ofs;
+        let mut kept = 0;
         while ofs < orig_write_pos {
             let v: &mut T = unsafe {
                 let meta = &mut self.data.as_mut()[ofs..];
@@ -234,6 +734,7 @@ impl<T: ?Sized, D: ::DataBuf> Fifo<T, D> {
                     }
                 }
                 writeback_pos += words;
+                kept += 1;
             } else {
                 // Don't update `writeback_pos`
                 // SAFE: Valid pointer, won't be accessed again
@@ -245,6 +746,7 @@ impl<T: ?Sized, D: ::DataBuf> Fifo<T, D> {
         }
         assert!(ofs == orig_write_pos);
         self.write_pos = writeback_pos;
+        self.count = kept;
     }
 }
 
@@ -261,29 +763,34 @@ struct PushInnerInfo<'a, DInner> {
 impl<T: ?Sized, D: ::DataBuf> Fifo<T, D> {
     /// Push an item to the list (setting metadata based on `fat_ptr`)
     /// UNSAFE: Caller must fill the buffer before any potential panic
-    unsafe fn push_inner(&mut self, fat_ptr: &T) -> Result<PushInnerInfo<D::Inner>, ()> {
+    unsafe fn push_inner(
+        &mut self,
+        fat_ptr: &T,
+    ) -> Result<PushInnerInfo<'_, D::Inner>, crate::PushError> {
         let bytes = mem::size_of_val(fat_ptr);
-        let (_data_ptr, len, v) = crate::decompose_pointer(fat_ptr);
+        let (_data_ptr, len, v) =
+            crate::decompose_pointer(fat_ptr).ok_or(crate::PushError::TooLarge)?;
         self.push_inner_raw(bytes, &v[..len])
     }
     unsafe fn push_inner_raw(
         &mut self,
         bytes: usize,
         metadata: &[usize],
-    ) -> Result<PushInnerInfo<D::Inner>, ()> {
+    ) -> Result<PushInnerInfo<'_, D::Inner>, crate::PushError> {
         let words = D::round_to_words(bytes) + Self::meta_words();
 
         // 1. Check if there's space for the item
         if self.space_words() < words {
             // 2. If not, check if compaction would help
             if self.space_words() + self.read_pos >= words {
-                self.compact();
+                self.compact_inner();
             }
             // 3. Then, try expanding
             if self.space_words() < words {
-                if let Err(_) = self.data.extend(self.write_pos + words) {
+                if let Err(_) = crate::extend_buf(&mut self.data, false, self.write_pos + words) {
                     // if expansion fails, return error
-                    return Err(());
+                    let used = self.write_pos - self.read_pos;
+                    return Err(crate::PushError::classify::<D>(words, used + words));
                 }
             }
         }
@@ -309,8 +816,89 @@ impl<T: ?Sized, D: ::DataBuf> Fifo<T, D> {
 }
 
 impl<D: ::DataBuf> Fifo<str, D> {
+    /// Construct a queue from a sequence of strings (pushed in order)
+    ///
+    /// ```
+    /// # use stack_dst::Fifo;
+    /// let queue = Fifo::<str, ::stack_dst::buffers::Ptr16>::from_strs(["a", "b", "c"].iter()).unwrap();
+    /// assert_eq!(queue.iter().collect::<Vec<_>>(), ["a", "b", "c"]);
+    /// ```
+    pub fn from_strs<I>(it: I) -> Result<Self, ()>
+    where
+        D: Default,
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut rv = Self::new();
+        rv.push_strs(it)?;
+        Ok(rv)
+    }
+    /// Push each string in order (the loop shared by `from_strs` and `clone_compact`)
+    fn push_strs<I>(&mut self, it: I) -> Result<(), ()>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        for v in it {
+            self.push_back_str(v.as_ref())?;
+        }
+        Ok(())
+    }
+    /// Clone the contents into a new queue with a different buffer type (e.g. moving from a
+    /// fixed-size buffer to a `Vec`)
+    ///
+    /// ```
+    /// # #[cfg(feature = "alloc")] {
+    /// # use stack_dst::Fifo;
+    /// let queue = Fifo::<str, ::stack_dst::buffers::Ptr8>::from_strs(["a", "b"].iter()).unwrap();
+    /// let queue2 = queue.clone_into_buffer::<::stack_dst::buffers::PtrVec>().unwrap();
+    /// assert_eq!(queue2.iter().collect::<Vec<_>>(), ["a", "b"]);
+    /// # }
+    /// ```
+    pub fn clone_into_buffer<D2: ::DataBuf + Default>(&self) -> Result<Fifo<str, D2>, ()> {
+        Fifo::from_strs(self.iter())
+    }
+    /// Clone the contents into a new queue, allocating exactly the space needed for the items
+    ///
+    /// Unlike `clone_into_buffer`, a growable `D2` won't over-allocate, and a fixed-size `D2`
+    /// is checked up-front (returning `Err` if the items don't fit).
+    ///
+    /// ```
+    /// # #[cfg(feature = "alloc")] {
+    /// # use stack_dst::Fifo;
+    /// let queue = Fifo::<str, ::stack_dst::buffers::PtrVec>::from_strs(["a", "b"].iter()).unwrap();
+    /// let queue2 = queue.clone_compact::<::stack_dst::buffers::PtrVec>().unwrap();
+    /// assert_eq!(queue2.capacity(), 4);
+    /// assert!(queue.clone_compact::<::stack_dst::buffers::Ptr2>().is_err());
+    /// # }
+    /// ```
+    pub fn clone_compact<D2: ::DataBuf + Default>(&self) -> Result<Fifo<str, D2>, ()> {
+        let words = self
+            .iter()
+            .map(|v| Fifo::<str, D2>::meta_words() + D2::round_to_words(v.len()))
+            .sum();
+        let mut rv = Fifo::new();
+        rv.reserve_exact(words)?;
+        rv.push_strs(self.iter())?;
+        Ok(rv)
+    }
     /// Push the contents of a string slice as an item onto the stack
     pub fn push_back_str(&mut self, v: &str) -> Result<(), ()> {
+        self.try_push_back_str(v).map_err(|_| ())
+    }
+    /// Push the contents of a string slice, reporting why the push failed
+    ///
+    /// ```
+    /// # use stack_dst::{Fifo, PushError};
+    /// let mut queue = Fifo::<str, ::stack_dst::buffers::U8_32>::new();
+    /// queue.try_push_back_str("Hello!").unwrap();
+    /// assert_eq!(queue.try_push_back_str(&"a".repeat(64)), Err(PushError::TooLarge));
+    /// ```
+    pub fn try_push_back_str(&mut self, v: &str) -> Result<(), crate::PushError> {
+        self.check_invariants();
+        self.try_push_back_str_inner(v)
+    }
+    fn try_push_back_str_inner(&mut self, v: &str) -> Result<(), crate::PushError> {
         unsafe {
             self.push_inner(v).map(|pii| {
                 ptr::copy(
@@ -318,7 +906,34 @@ impl<D: ::DataBuf> Fifo<str, D> {
                     pii.data.as_mut_ptr() as *mut u8,
                     v.len(),
                 )
-            })
+            })?;
+        }
+        self.count += 1;
+        Ok(())
+    }
+    /// Push the contents of a string slice, removing items from the front to make space if needed
+    ///
+    /// See `push_back_evicting` for details.
+    ///
+    /// ```
+    /// let mut queue = ::stack_dst::Fifo::<str, ::stack_dst::buffers::U8_32>::new();
+    /// assert_eq!(queue.push_back_str_evicting("Hello"), Ok(None));
+    /// assert_eq!(queue.push_back_str_evicting(&"a".repeat(20)), Ok(Some(())));
+    /// assert_eq!(queue.len(), 1);
+    /// ```
+    pub fn push_back_str_evicting(&mut self, v: &str) -> Result<Option<()>, ()> {
+        self.check_invariants();
+        let mut evicted = false;
+        loop {
+            match self.try_push_back_str_inner(v) {
+                Ok(()) => return Ok(if evicted { Some(()) } else { None }),
+                Err(e) => {
+                    if !self.evict_for_retry(e) {
+                        return Err(());
+                    }
+                    evicted = true;
+                }
+            }
         }
     }
 }
@@ -327,6 +942,76 @@ impl<D: ::DataBuf, T: Clone> Fifo<[T], D>
 where
     (T, D::Inner): crate::AlignmentValid,
 {
+    /// Construct a queue from a sequence of slices (cloning the items, pushed in order)
+    ///
+    /// ```
+    /// # use stack_dst::Fifo;
+    /// let queue = Fifo::<[u8], ::stack_dst::buffers::Ptr8>::from_slices(vec![vec![1, 2], vec![3]]).unwrap();
+    /// assert_eq!(queue.front(), Some(&[1, 2][..]));
+    /// ```
+    pub fn from_slices<I>(it: I) -> Result<Self, ()>
+    where
+        D: Default,
+        I: IntoIterator,
+        I::Item: AsRef<[T]>,
+    {
+        let mut rv = Self::new();
+        rv.push_slices(it)?;
+        Ok(rv)
+    }
+    /// Push clones of each slice in order (the loop shared by `from_slices` and `clone_compact`)
+    fn push_slices<I>(&mut self, it: I) -> Result<(), ()>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[T]>,
+    {
+        for v in it {
+            self.push_cloned(v.as_ref())?;
+        }
+        Ok(())
+    }
+    /// Clone the contents into a new queue with a different buffer type (e.g. moving from a
+    /// fixed-size buffer to a `Vec`)
+    ///
+    /// ```
+    /// # #[cfg(feature = "alloc")] {
+    /// # use stack_dst::Fifo;
+    /// let queue = Fifo::<[u8], ::stack_dst::buffers::Ptr8>::from_slices(vec![vec![1, 2], vec![3]]).unwrap();
+    /// let queue2 = queue.clone_into_buffer::<::stack_dst::buffers::U8Vec>().unwrap();
+    /// assert_eq!(queue2.iter().collect::<Vec<_>>(), [&[1, 2][..], &[3][..]]);
+    /// # }
+    /// ```
+    pub fn clone_into_buffer<D2: ::DataBuf + Default>(&self) -> Result<Fifo<[T], D2>, ()>
+    where
+        (T, D2::Inner): crate::AlignmentValid,
+    {
+        Fifo::from_slices(self.iter())
+    }
+    /// Clone the contents into a new queue, allocating exactly the space needed for the items
+    ///
+    /// See `Fifo::<str>::clone_compact`
+    ///
+    /// ```
+    /// # #[cfg(feature = "alloc")] {
+    /// # use stack_dst::Fifo;
+    /// let queue = Fifo::<[u8], ::stack_dst::buffers::PtrVec>::from_slices(vec![vec![1, 2], vec![3]]).unwrap();
+    /// let queue2 = queue.clone_compact::<::stack_dst::buffers::U8Vec>().unwrap();
+    /// assert_eq!(queue2.iter().collect::<Vec<_>>(), [&[1, 2][..], &[3][..]]);
+    /// # }
+    /// ```
+    pub fn clone_compact<D2: ::DataBuf + Default>(&self) -> Result<Fifo<[T], D2>, ()>
+    where
+        (T, D2::Inner): crate::AlignmentValid,
+    {
+        let words = self
+            .iter()
+            .map(|v| Fifo::<[T], D2>::meta_words() + D2::round_to_words(mem::size_of_val(v)))
+            .sum();
+        let mut rv = Fifo::new();
+        rv.reserve_exact(words)?;
+        rv.push_slices(self.iter())?;
+        Ok(rv)
+    }
     /// Pushes a set of items (cloning out of the input slice)
     ///
     /// ```
@@ -349,17 +1034,22 @@ where
     where
         T: Copy,
     {
+        self.check_invariants();
         <(T, D::Inner) as crate::AlignmentValid>::check();
         // SAFE: Carefully constructed to maintain consistency
         unsafe {
-            self.push_inner(v).map(|pii| {
-                ptr::copy(
-                    v.as_ptr() as *const u8,
-                    pii.data.as_mut_ptr() as *mut u8,
-                    mem::size_of_val(v),
-                )
-            })
+            self.push_inner(v)
+                .map(|pii| {
+                    ptr::copy(
+                        v.as_ptr() as *const u8,
+                        pii.data.as_mut_ptr() as *mut u8,
+                        mem::size_of_val(v),
+                    )
+                })
+                .map_err(|_| ())?;
         }
+        self.count += 1;
+        Ok(())
     }
 }
 impl<D: crate::DataBuf, T> Fifo<[T], D>
@@ -378,10 +1068,13 @@ where
     /// assert_eq!(stack.front().unwrap(), &[0,1,2,3,4,5,6,7,8,9]);
     /// ```
     pub fn push_from_iter(&mut self, mut iter: impl ExactSizeIterator<Item = T>) -> Result<(), ()> {
+        self.check_invariants();
         <(T, D::Inner) as crate::AlignmentValid>::check();
         // SAFE: API used correctly
         unsafe {
-            let pii = self.push_inner_raw(iter.len() * mem::size_of::<T>(), &[0])?;
+            let pii = self
+                .push_inner_raw(iter.len() * mem::size_of::<T>(), &[0])
+                .map_err(|_| ())?;
             crate::list_push_gen(
                 pii.meta,
                 pii.data,
@@ -390,14 +1083,59 @@ where
                 pii.reset_slot,
                 pii.reset_value,
             );
-            Ok(())
         }
+        self.count += 1;
+        Ok(())
     }
 }
 
 impl<T: ?Sized, D: crate::DataBuf> ops::Drop for Fifo<T, D> {
     fn drop(&mut self) {
-        while let Some(_) = self.pop_front() {}
+        self.clear();
+    }
+}
+impl<D: ::DataBuf + Default> Clone for Fifo<str, D> {
+    fn clone(&self) -> Self {
+        let mut rv = Self::new();
+        rv.clone_from(self);
+        rv
+    }
+    /// Clone the items from `source`, reusing the existing buffer
+    ///
+    /// ```
+    /// # use stack_dst::Fifo;
+    /// let mut queue = Fifo::<str, ::stack_dst::buffers::Ptr8>::new();
+    /// queue.push_back_str("Hello");
+    /// let mut other = Fifo::new();
+    /// other.push_back_str("Goodbye");
+    /// other.clone_from(&queue);
+    /// assert_eq!(other.front(), Some("Hello"));
+    /// assert_eq!(other.len(), 1);
+    /// ```
+    fn clone_from(&mut self, source: &Self) {
+        self.clear();
+        for v in source.iter() {
+            self.push_back_str(v)
+                .expect("Fifo::clone_from - Unable to push item");
+        }
+    }
+}
+impl<D: ::DataBuf + Default, T: Clone> Clone for Fifo<[T], D>
+where
+    (T, D::Inner): crate::AlignmentValid,
+{
+    fn clone(&self) -> Self {
+        let mut rv = Self::new();
+        rv.clone_from(self);
+        rv
+    }
+    /// Clone the items from `source`, reusing the existing buffer
+    fn clone_from(&mut self, source: &Self) {
+        self.clear();
+        for v in source.iter() {
+            self.push_cloned(v)
+                .expect("Fifo::clone_from - Unable to push item");
+        }
     }
 }
 impl<T: ?Sized, D: ::DataBuf + Default> Default for Fifo<T, D> {
@@ -428,7 +1166,7 @@ impl<'a, T: ?Sized, D: crate::DataBuf> ops::Drop for PopHandle<'a, T, D> {
 }
 
 /// DST FIFO iterator (immutable)
-pub struct Iter<'a, T: 'a + ?Sized, D: 'a + crate::DataBuf>(&'a Fifo<T, D>, usize);
+pub struct Iter<'a, T: 'a + ?Sized, D: 'a + crate::DataBuf>(&'a Fifo<T, D>, usize, usize);
 impl<'a, T: 'a + ?Sized, D: 'a + crate::DataBuf> iter::Iterator for Iter<'a, T, D> {
     type Item = &'a T;
     fn next(&mut self) -> Option<&'a T> {
@@ -438,12 +1176,33 @@ impl<'a, T: 'a + ?Sized, D: 'a + crate::DataBuf> iter::Iterator for Iter<'a, T,
             // SAFE: Bounds checked, aliasing enforced by API
             let rv = unsafe { &*self.0.raw_at(self.1) };
             self.1 += Fifo::<T, D>::meta_words() + D::round_to_words(mem::size_of_val(rv));
+            self.2 -= 1;
             Some(rv)
         }
     }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.2, Some(self.2))
+    }
+}
+impl<'a, T: 'a + ?Sized, D: 'a + crate::DataBuf> iter::ExactSizeIterator for Iter<'a, T, D> {}
+/// DST FIFO iterator yielding offsets along with items (see `Fifo::iter_with_offsets`)
+pub struct IterWithOffsets<'a, T: 'a + ?Sized, D: 'a + crate::DataBuf>(Iter<'a, T, D>);
+impl<'a, T: 'a + ?Sized, D: 'a + crate::DataBuf> iter::Iterator for IterWithOffsets<'a, T, D> {
+    type Item = (usize, &'a T);
+    fn next(&mut self) -> Option<(usize, &'a T)> {
+        let ofs = self.0 .1;
+        self.0.next().map(|v| (ofs, v))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<'a, T: 'a + ?Sized, D: 'a + crate::DataBuf> iter::ExactSizeIterator
+    for IterWithOffsets<'a, T, D>
+{
 }
 /// DST FIFO iterator (mutable)
-pub struct IterMut<'a, T: 'a + ?Sized, D: 'a + crate::DataBuf>(&'a mut Fifo<T, D>, usize);
+pub struct IterMut<'a, T: 'a + ?Sized, D: 'a + crate::DataBuf>(&'a mut Fifo<T, D>, usize, usize);
 impl<'a, T: 'a + ?Sized, D: 'a + crate::DataBuf> iter::Iterator for IterMut<'a, T, D> {
     type Item = &'a mut T;
     fn next(&mut self) -> Option<&'a mut T> {
@@ -453,7 +1212,12 @@ impl<'a, T: 'a + ?Sized, D: 'a + crate::DataBuf> iter::Iterator for IterMut<'a,
             // SAFE: Bounds checked, aliasing enforced by API
             let rv = unsafe { &mut *self.0.raw_at_mut(self.1) };
             self.1 += Fifo::<T, D>::meta_words() + D::round_to_words(mem::size_of_val(rv));
+            self.2 -= 1;
             Some(rv)
         }
     }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.2, Some(self.2))
+    }
 }
+impl<'a, T: 'a + ?Sized, D: 'a + crate::DataBuf> iter::ExactSizeIterator for IterMut<'a, T, D> {}
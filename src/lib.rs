@@ -24,7 +24,7 @@
 //! ```rust
 //! # use stack_dst::Value;
 //! #
-//! fn make_closure(value: u64) -> Value<dyn FnMut()->String, ::stack_dst::array_buf![u64; U2]> {
+//! fn make_closure(value: u64) -> Value<dyn FnMut()->String, ::stack_dst::buffers::U64_2> {
 //!     Value::new_stable(move || format!("Hello there! value={}", value), |p| p as _)
 //!         .ok().expect("Closure doesn't fit")
 //! }
@@ -47,22 +47,37 @@
 //! ```rust
 //! # use stack_dst::Value;
 //! # use std::any::Any;
-//! let v: Value<dyn Any, ::stack_dst::array_buf![u128; U2]> =
+//! let v: Value<dyn Any, ::stack_dst::buffers::Align16_U8_32> =
 //!     Value::new_stable(123i128, |p| p as _).unwrap();
 //! ```
 //!
 //! # Feature flags
 //! ## `alloc` (default)
 //! Provides the `StackDstA::new_or_boxed` method (if `unsize` feature is active too)
+//! ## `std` (optional)
+//! Provides the [sync] module, containing thread-safe wrappers
 //! ## `const_generics` (default)
 //! Uses value/constant generics to provide a slightly nicer API (e.g. [ValueU])
+//! ## `typenum_buffers` (optional)
+//! Provides the `typenum`-based `buffers::ArrayBuf` and the `array_buf!` macro (pulls in
+//! `generic-array`). When enabled, the buffer aliases (e.g. `buffers::Ptr8`) use this type instead
+//! of the const-generic `buffers::ConstArrayBuf`
+//! ## `core_error` (optional)
+//! Provides helpers for storing `core::error::Error` values (requires rust 1.81)
+//! ## `try_reserve` (optional)
+//! Provides `Value::try_reserve` for `Vec`-backed slices, reporting allocation failure instead
+//! of aborting (requires rust 1.57)
 //! ## `unsize` (optional)
 //! Uses the nightly feature `unsize` to provide a more egonomic API
 //! (no need for the `|p| p` closures)
+//! ## `ptr_metadata` (optional)
+//! Uses the nightly feature `ptr_metadata` to split and re-create fat pointers, instead of
+//! assuming that they're laid out as the data pointer followed by the metadata
 // //! ## `full_const_generics` (optional)
 // //! Uses extended const generics to give compile time alignment errors
 //!
 #![cfg_attr(feature = "unsize", feature(unsize))] // needed for Unsize
+#![cfg_attr(feature = "ptr_metadata", feature(ptr_metadata))] // needed for `ptr::metadata`
 #![cfg_attr(feature = "full_const_generics", feature(generic_const_exprs))]
 #![cfg_attr(feature = "full_const_generics", allow(incomplete_features))]
 #![no_std]
@@ -74,18 +89,21 @@
 )]
 
 use core::mem::MaybeUninit;
-use core::{mem, ptr, slice};
+use core::{fmt, mem, ptr};
 
 // Internal helper
 type BufSlice<T> = [MaybeUninit<T>];
 
-#[cfg(miri)]
+#[cfg(any(miri, feature = "std"))]
 #[macro_use]
 extern crate std;
 
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
+// generic-array 0.14 is deprecated upstream, but is still used by `typenum_buffers`
+#[cfg(feature = "typenum_buffers")]
+#[allow(deprecated, clippy::useless_attribute)]
 extern crate generic_array;
 
 mod data_buf;
@@ -93,19 +111,82 @@ pub use self::data_buf::DataBuf;
 pub use self::data_buf::Pod;
 
 pub use fifo::Fifo;
+pub use stack::FixedStack;
 pub use stack::Stack;
 pub use value::Value;
+pub use value::ValueRef;
 
 /// Shorthand for defining a array buffer
 ///
 /// The array size must be a typenum unsigned integer (e.g `U8`)
 /// E.g. `array_buf![u8; U32]` expands to
 /// `::stack_dst::buffers::ArrayBuf<u8, ::stack_dst::buffers::n::::U32>`
+///
+/// Requires the `typenum_buffers` feature
+#[cfg(feature = "typenum_buffers")]
 #[macro_export]
 macro_rules! array_buf {
     ($t:ty; $n:ident) => { $crate::buffers::ArrayBuf<$t, $crate::buffers::n::$n> }
 }
 
+/// Construct a `Value` (without needing the `unsize` feature or a coercion closure)
+///
+/// `coerce!(Value<dyn Trait, Buf>, expr)` expands to
+/// `Value::<dyn Trait, Buf>::new_stable(expr, |p| p)`, so evaluates to a `Result` with the
+/// original value returned on failure.
+///
+/// ```
+/// # #[macro_use] extern crate stack_dst;
+/// # use stack_dst::Value;
+/// # use std::fmt::Display;
+/// # fn main() {
+/// let v = coerce!(Value<dyn Display, ::stack_dst::buffers::Ptr2>, 1234).unwrap();
+/// assert_eq!(v.to_string(), "1234");
+/// # }
+/// ```
+///
+/// Slices work the same way (coercing from an array)
+/// ```
+/// # #[macro_use] extern crate stack_dst;
+/// # use stack_dst::Value;
+/// # fn main() {
+/// let v = coerce!(Value<[u8], ::stack_dst::buffers::Ptr2>, [1, 2, 3]).unwrap();
+/// assert_eq!(&v[..], &[1, 2, 3]);
+/// assert!(coerce!(Value<[u8], ::stack_dst::buffers::Ptr2>, [0; 64]).is_err());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! coerce {
+    ($t:ty, $e:expr) => {
+        <$t>::new_stable($e, |p| p)
+    };
+}
+
+/// Fail to compile if a `Value`, `Stack`, or `Fifo` type is larger than `max` bytes
+///
+/// Uses the type's `FOOTPRINT` constant, and must be used where a statement is allowed (e.g.
+/// in a function body).
+///
+/// ```
+/// # #[macro_use] extern crate stack_dst;
+/// # fn main() {
+/// assert_footprint!(stack_dst::Value<dyn std::any::Any, ::stack_dst::buffers::Ptr8>, 64);
+/// # }
+/// ```
+///
+/// ```compile_fail
+/// # #[macro_use] extern crate stack_dst;
+/// # fn main() {
+/// assert_footprint!(stack_dst::Value<dyn std::any::Any, ::stack_dst::buffers::Ptr16>, 32);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! assert_footprint {
+    ($t:ty, $max:expr) => {
+        let _: [(); 0] = [(); (<$t>::FOOTPRINT > $max) as usize];
+    };
+}
+
 pub mod buffers {
     //! Type aliases for common buffer sizes and types
     //!
@@ -114,12 +195,28 @@ pub mod buffers {
     //!   (a good balance of space used)
     //! - [`Ptr2`] is suitable for storing a single pointer and its vtable
 
+    #[cfg(feature = "typenum_buffers")]
     pub use self::array_buf::ArrayBuf;
-    #[cfg(feature = "const_generics")]
     pub use self::cg_array_buf::ArrayBuf as ConstArrayBuf;
+    /// A fixed-size buffer of `N` words of `T`, for any `N`
+    ///
+    /// Recommended over bare `[MaybeUninit<T>; N]` arrays, as those only implement `DataBuf` for
+    /// a limited set of sizes when the `const_generics` feature is disabled.
+    ///
+    /// ```
+    /// # use stack_dst::{Value, buffers::Array};
+    /// let val = Value::<[u8], _>::in_buffer_stable(Array::<u64, 5>::new(), [1, 2, 3], |v| v)
+    ///     .ok()
+    ///     .expect("Insufficient size");
+    /// assert_eq!(&val[..], &[1, 2, 3]);
+    /// ```
+    pub type Array<T, const N: usize> = self::cg_array_buf::ArrayBuf<T, N>;
     /// A re-export of `typenum` for shorter names
+    #[cfg(feature = "typenum_buffers")]
     pub use generic_array::typenum as n;
 
+    #[cfg(feature = "typenum_buffers")]
+    #[allow(deprecated)]
     mod array_buf {
         use core::mem::MaybeUninit;
 
@@ -163,23 +260,31 @@ pub mod buffers {
                     Ok(())
                 }
             }
+            fn max_words() -> usize {
+                N::USIZE
+            }
         }
     }
 
-    #[cfg(feature = "const_generics")]
     mod cg_array_buf {
         /// A buffer backing onto an array (used to provide default) - using constant generics
         pub struct ArrayBuf<T, const N: usize> {
             inner: [::core::mem::MaybeUninit<T>; N],
         }
+        impl<T: crate::Pod, const N: usize> ArrayBuf<T, N> {
+            /// Construct a new (uninitialised) buffer
+            pub fn new() -> Self {
+                ArrayBuf {
+                    inner: [::core::mem::MaybeUninit::uninit(); N],
+                }
+            }
+        }
         impl<T, const N: usize> ::core::default::Default for ArrayBuf<T, N>
         where
             T: crate::Pod,
         {
             fn default() -> Self {
-                ArrayBuf {
-                    inner: [::core::mem::MaybeUninit::uninit(); N],
-                }
+                Self::new()
             }
         }
         unsafe impl<T, const N: usize> crate::DataBuf for ArrayBuf<T, N>
@@ -200,26 +305,279 @@ pub mod buffers {
                     Ok(())
                 }
             }
+            fn max_words() -> usize {
+                N
+            }
         }
     }
 
-    /// 8 pointers (32/64 bytes, with pointer alignment)
-    pub type Ptr8 = ArrayBuf<usize, n::U8>;
-    /// 64 bytes, 64-bit alignment
-    pub type U64_8 = ArrayBuf<u64, n::U8>;
-    /// 32 bytes, 8-bit alignment
-    pub type U8_32 = ArrayBuf<u8, n::U32>;
+    /// Wrapper that aligns a buffer to 16 bytes
+    ///
+    /// If the wrapped buffer stores its data inline, a `Value` using it can hold types
+    /// needing up to 16 byte alignment, while keeping the granularity of the inner buffer.
+    /// (The extra alignment only applies to `Value`, as `Stack`/`Fifo` store data at
+    /// arbitrary word offsets)
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// # use std::any::Any;
+    /// let v: Value<dyn Any, ::stack_dst::buffers::Align16_U8_32> =
+    ///     Value::new_stable(123i128, |p| p as _).unwrap();
+    /// assert_eq!(v.downcast_ref::<i128>(), Some(&123));
+    /// ```
+    #[repr(align(16))]
+    #[derive(Default)]
+    pub struct A16<B>(pub B);
 
-    /// 16 bytes, 64-bit alignment
-    pub type U64_2 = ArrayBuf<u64, n::U2>;
+    /// `DataBuf` implementation for the over-aligning wrappers
+    macro_rules! impl_aligned_wrapper {
+        ($name:ident) => {
+            unsafe impl<B: crate::DataBuf> crate::DataBuf for $name<B> {
+                type Inner = B::Inner;
+                fn as_ref(&self) -> &[::core::mem::MaybeUninit<Self::Inner>] {
+                    self.0.as_ref()
+                }
+                fn as_mut(&mut self) -> &mut [::core::mem::MaybeUninit<Self::Inner>] {
+                    self.0.as_mut()
+                }
+                fn extend(&mut self, len: usize) -> Result<(), ()> {
+                    self.0.extend(len)
+                }
+                fn extend_exact(&mut self, len: usize) -> Result<(), ()> {
+                    self.0.extend_exact(len)
+                }
+                fn on_extend(&mut self, old: usize, new: usize) {
+                    self.0.on_extend(old, new)
+                }
+                fn max_words() -> usize {
+                    B::max_words()
+                }
+                fn data_alignment(&self) -> usize {
+                    let base = self as *const _ as usize;
+                    let data = self.0.as_ref().as_ptr() as usize;
+                    let inner = self.0.data_alignment();
+                    if base <= data && data < base + ::core::mem::size_of::<Self>() {
+                        // Inline data: alignment is set by the offset within this wrapper
+                        let ofs = data - base;
+                        let ofs_align = if ofs == 0 {
+                            ::core::mem::align_of::<Self>()
+                        } else {
+                            1 << ofs.trailing_zeros()
+                        };
+                        ::core::cmp::max(
+                            inner,
+                            ::core::cmp::min(ofs_align, ::core::mem::align_of::<Self>()),
+                        )
+                    } else {
+                        inner
+                    }
+                }
+            }
+        };
+    }
+    impl_aligned_wrapper!(A16);
+    impl_aligned_wrapper!(CacheLine);
 
-    /// 16 pointers (64/128 bytes, with pointer alignment)
-    pub type Ptr16 = ArrayBuf<usize, n::U16>;
+    /// Wrapper that aligns a buffer to a 64 byte cache line
+    ///
+    /// Useful when values touched by different threads (e.g. in an array of `Value`s) should
+    /// each be on their own cache line. Otherwise behaves as `A16`.
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// # use stack_dst::buffers::{CacheLine, Ptr8};
+    /// let v = Value::<[u8], CacheLine<Ptr8>>::new_stable([1, 2, 3], |p| p).unwrap();
+    /// assert_eq!(&*v as *const [u8] as *const u8 as usize % 64, 0);
+    /// ```
+    #[repr(align(64))]
+    #[derive(Default)]
+    pub struct CacheLine<B>(pub B);
 
-    /// Two pointers, useful for wrapping a pointer along with a vtable
-    pub type Ptr2 = ArrayBuf<usize, n::U2>;
-    /// One pointer, can only store the vtable
-    pub type Ptr1 = ArrayBuf<usize, n::U1>;
+    /// Wrapper that calls a closure each time the buffer grows (with the old and new sizes in words)
+    ///
+    /// Useful to measure how often a buffer spills/reallocates.
+    ///
+    /// ```
+    /// # #[cfg(feature = "alloc")] {
+    /// # use stack_dst::Stack;
+    /// # use stack_dst::buffers::{Observed, PtrVec};
+    /// let mut n_grows = 0;
+    /// {
+    ///     let buf = Observed(PtrVec::new(), |_old, _new| n_grows += 1);
+    ///     let mut stack = Stack::<str, _>::with_buffer(buf);
+    ///     stack.push_str("Hello").unwrap();
+    /// }
+    /// assert_eq!(n_grows, 1);
+    /// # }
+    /// ```
+    pub struct Observed<B, F>(pub B, pub F);
+    unsafe impl<B: crate::DataBuf, F: FnMut(usize, usize)> crate::DataBuf for Observed<B, F> {
+        type Inner = B::Inner;
+        fn as_ref(&self) -> &[::core::mem::MaybeUninit<Self::Inner>] {
+            self.0.as_ref()
+        }
+        fn as_mut(&mut self) -> &mut [::core::mem::MaybeUninit<Self::Inner>] {
+            self.0.as_mut()
+        }
+        fn extend(&mut self, len: usize) -> Result<(), ()> {
+            self.0.extend(len)
+        }
+        fn extend_exact(&mut self, len: usize) -> Result<(), ()> {
+            self.0.extend_exact(len)
+        }
+        fn on_extend(&mut self, old: usize, new: usize) {
+            (self.1)(old, new);
+            self.0.on_extend(old, new)
+        }
+        fn max_words() -> usize {
+            B::max_words()
+        }
+        fn data_alignment(&self) -> usize {
+            self.0.data_alignment()
+        }
+    }
+
+    /// Adapter to use any slice-backed storage (e.g. a third-party inline vector) as a buffer
+    ///
+    /// The words are accessed through `S`'s `AsRef`/`AsMut` implementations, and the buffer can't
+    /// grow past the length of that slice.
+    ///
+    /// ```
+    /// # use stack_dst::Stack;
+    /// # use stack_dst::buffers::FromSlice;
+    /// # use std::mem::MaybeUninit;
+    /// struct Storage([MaybeUninit<usize>; 4]);
+    /// impl AsRef<[MaybeUninit<usize>]> for Storage {
+    ///     fn as_ref(&self) -> &[MaybeUninit<usize>] { &self.0 }
+    /// }
+    /// impl AsMut<[MaybeUninit<usize>]> for Storage {
+    ///     fn as_mut(&mut self) -> &mut [MaybeUninit<usize>] { &mut self.0 }
+    /// }
+    /// // SAFE: Both methods return the same (inline) array
+    /// let buf = unsafe { FromSlice::new(Storage([MaybeUninit::uninit(); 4])) };
+    /// let mut stack = Stack::<str, _>::with_buffer(buf);
+    /// stack.push_str("Hello").unwrap();
+    /// assert_eq!(stack.top(), Some("Hello"));
+    /// ```
+    pub struct FromSlice<S, T = usize> {
+        inner: S,
+        _pd: ::core::marker::PhantomData<T>,
+    }
+    impl<S, T> FromSlice<S, T>
+    where
+        S: AsRef<[::core::mem::MaybeUninit<T>]> + AsMut<[::core::mem::MaybeUninit<T>]>,
+    {
+        /// Wrap a slice-backed storage type
+        ///
+        /// UNSAFE: `S` must uphold the rules of `DataBuf` - its `as_ref` and `as_mut` must always
+        /// return the same slice, and that slice must not move unless `S` itself is moved.
+        pub unsafe fn new(inner: S) -> Self {
+            FromSlice {
+                inner,
+                _pd: ::core::marker::PhantomData,
+            }
+        }
+        /// Obtain the wrapped storage
+        pub fn into_inner(self) -> S {
+            self.inner
+        }
+    }
+    unsafe impl<S, T: crate::Pod> crate::DataBuf for FromSlice<S, T>
+    where
+        S: AsRef<[::core::mem::MaybeUninit<T>]> + AsMut<[::core::mem::MaybeUninit<T>]>,
+    {
+        type Inner = T;
+        fn as_ref(&self) -> &[::core::mem::MaybeUninit<Self::Inner>] {
+            self.inner.as_ref()
+        }
+        fn as_mut(&mut self) -> &mut [::core::mem::MaybeUninit<Self::Inner>] {
+            self.inner.as_mut()
+        }
+        fn extend(&mut self, len: usize) -> Result<(), ()> {
+            if len > self.inner.as_ref().len() {
+                Err(())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    /// Defines a fixed-size buffer alias, backed by `ArrayBuf` when `typenum_buffers` is enabled
+    /// and by `ConstArrayBuf` otherwise
+    macro_rules! array_alias {
+        ( $(#[$a:meta])* $name:ident = $t:ty; $n:ident, $cn:tt ) => {
+            $(#[$a])*
+            #[cfg(feature = "typenum_buffers")]
+            pub type $name = ArrayBuf<$t, n::$n>;
+            $(#[$a])*
+            #[cfg(not(feature = "typenum_buffers"))]
+            pub type $name = ConstArrayBuf<$t, $cn>;
+        };
+    }
+
+    array_alias! {
+        /// 8 pointers (32/64 bytes, with pointer alignment)
+        Ptr8 = usize; U8, 8
+    }
+    array_alias! {
+        /// 64 bytes, 64-bit alignment
+        U64_8 = u64; U8, 8
+    }
+    array_alias! {
+        /// 32 bytes, 8-bit alignment
+        U8_32 = u8; U32, 32
+    }
+
+    array_alias! {
+        /// 16 bytes, 64-bit alignment
+        U64_2 = u64; U2, 2
+    }
+
+    /// 32 bytes, 8-bit granularity with 16 byte alignment
+    #[allow(non_camel_case_types)]
+    pub type Align16_U8_32 = A16<U8_32>;
+    /// 64 bytes, 8-bit granularity with 16 byte alignment
+    #[allow(non_camel_case_types)]
+    pub type Align16_U8_64 = A16<U8_64>;
+    array_alias! {
+        /// 64 bytes, 8-bit alignment
+        U8_64 = u8; U64, 64
+    }
+
+    array_alias! {
+        /// 16 pointers (64/128 bytes, with pointer alignment)
+        Ptr16 = usize; U16, 16
+    }
+
+    array_alias! {
+        /// Two pointers, useful for wrapping a pointer along with a vtable
+        Ptr2 = usize; U2, 2
+    }
+    /// Buffer for a single trait object with up to one word of data (plus the vtable)
+    ///
+    /// For larger types, see `for_trait_object`.
+    pub type TraitObject = Ptr2;
+
+    /// Returns the number of pointer-sized words needed to store a `U` as a trait object (i.e. the
+    /// data rounded up to words, plus one word for the vtable)
+    ///
+    /// ```
+    /// # use stack_dst::buffers::{for_trait_object, Ptr2, Ptr8};
+    /// # use std::fmt::Debug;
+    /// assert_eq!(for_trait_object::<[u32; 5]>(), 4);
+    /// let v = stack_dst::Value::<dyn Debug, Ptr2>::new_stable([1u32; 5], |p| p as _);
+    /// assert!(v.is_err());
+    /// let v = stack_dst::Value::<dyn Debug, Ptr8>::new_stable([1u32; 5], |p| p as _);
+    /// assert!(v.is_ok());
+    /// ```
+    pub const fn for_trait_object<U>() -> usize {
+        let ptr_size = ::core::mem::size_of::<usize>();
+        (::core::mem::size_of::<U>() + ptr_size - 1) / ptr_size + 1
+    }
+    array_alias! {
+        /// One pointer, can only store the vtable
+        Ptr1 = usize; U1, 1
+    }
 
     /// Dyanamically allocated buffer with 8-byte alignment
     #[cfg(feature = "alloc")]
@@ -230,12 +588,67 @@ pub mod buffers {
     /// Dyanamically allocated buffer with pointer alignment
     #[cfg(feature = "alloc")]
     pub type PtrVec = ::alloc::vec::Vec<::core::mem::MaybeUninit<usize>>;
+
+    /// `Vec`-backed buffer that at least doubles in size when it grows
+    ///
+    /// The plain `Vec` buffer only grows to the requested size (then to the current capacity),
+    /// keeping memory use minimal. This instead ensures amortised growth for many small pushes.
+    ///
+    /// ```
+    /// let mut buf = ::stack_dst::Fifo::<str, ::stack_dst::buffers::GrowableVec<u8>>::new();
+    /// for _ in 0..100 {
+    ///     buf.push_back_str("Hello world!").unwrap();
+    /// }
+    /// assert_eq!(buf.len(), 100);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub struct GrowableVec<T>(::alloc::vec::Vec<::core::mem::MaybeUninit<T>>);
+    #[cfg(feature = "alloc")]
+    impl<T> GrowableVec<T> {
+        /// Construct a new empty buffer
+        pub fn new() -> Self {
+            GrowableVec(::alloc::vec::Vec::new())
+        }
+    }
+    #[cfg(feature = "alloc")]
+    impl<T> Default for GrowableVec<T> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+    #[cfg(feature = "alloc")]
+    unsafe impl<T: crate::Pod> crate::DataBuf for GrowableVec<T> {
+        type Inner = T;
+        fn as_ref(&self) -> &[::core::mem::MaybeUninit<T>] {
+            &self.0
+        }
+        fn as_mut(&mut self) -> &mut [::core::mem::MaybeUninit<T>] {
+            &mut self.0
+        }
+        fn extend(&mut self, len: usize) -> Result<(), ()> {
+            if len > self.0.len() {
+                let new_len = ::core::cmp::max(len, self.0.len() * 2);
+                self.0.resize(new_len, ::core::mem::MaybeUninit::uninit());
+            }
+            Ok(())
+        }
+        fn extend_exact(&mut self, len: usize) -> Result<(), ()> {
+            self.0.extend_exact(len)
+        }
+    }
 }
 
 /// Implementation of the FIFO list structure
 pub mod fifo;
+/// Low-level fat pointer helpers, for building custom DST containers
+pub mod raw;
 /// Implementation of the LIFO stack structure
 pub mod stack;
+/// Thread-safe wrappers (requires the `std` feature)
+#[cfg(feature = "std")]
+pub mod sync;
+/// Traits shared by the collection and value types
+pub mod traits;
 /// Implementation of the single-value structure
 pub mod value;
 
@@ -266,33 +679,184 @@ pub type StackU<T /*: ?Sized*/, const N: usize /* = 16*/> =
 pub type FifoU<T /*: ?Sized*/, const N: usize /* = {8+1}*/> =
     Fifo<T, buffers::ConstArrayBuf<usize, N>>;
 
-fn decompose_pointer<T: ?Sized>(mut ptr: *const T) -> (*const (), usize, [usize; 3]) {
+/// Reason for a push into a `Stack` or `Fifo` failing
+///
+/// All fallible constructors and push/append methods return a `Result` (which is `#[must_use]`),
+/// so ignoring a failure to store a value is reported by the `unused_must_use` lint.
+///
+/// ```compile_fail
+/// #![deny(unused_must_use)]
+/// let mut stack = ::stack_dst::Stack::<str, ::stack_dst::buffers::Ptr2>::new();
+/// // Error: unused `Result` that must be used
+/// stack.push_str("This string doesn't fit");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PushError {
+    /// There isn't enough free space right now (popping items would make space)
+    Full,
+    /// The item is larger than the buffer could ever hold
+    TooLarge,
+    /// The buffer could hold the item, but failed to grow
+    AllocFailed,
+    /// The item requires a stricter alignment than the buffer provides
+    Misaligned,
+    /// The item's size differs from the items already stored (see `FixedStack`)
+    SizeMismatch,
+}
+impl PushError {
+    /// Classify a failed push of `words` words, given the total words needed once the item is in
+    fn classify<D: DataBuf>(words: usize, total_words: usize) -> PushError {
+        if words > D::max_words() {
+            PushError::TooLarge
+        } else if total_words > D::max_words() {
+            PushError::Full
+        } else {
+            PushError::AllocFailed
+        }
+    }
+}
+impl fmt::Display for PushError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            PushError::Full => "insufficient free space",
+            PushError::TooLarge => "value is larger than the buffer",
+            PushError::AllocFailed => "failed to grow buffer",
+            PushError::Misaligned => "value alignment exceeds buffer alignment",
+            PushError::SizeMismatch => "value size differs from the stored items",
+        })
+    }
+}
+#[cfg(feature = "std")]
+impl ::std::error::Error for PushError {}
+
+/// Error from a failed push or construction, holding the value that couldn't be stored
+///
+/// ```
+/// # use stack_dst::{Stack, PushError};
+/// let mut stack = Stack::<[u8], ::stack_dst::buffers::Ptr2>::new();
+/// let e = stack.try_push_stable([0u8; 64], |v| v).unwrap_err();
+/// assert_eq!(e.kind(), PushError::TooLarge);
+/// assert_eq!(e.into_inner(), [0u8; 64]);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CapacityError<U> {
+    value: U,
+    kind: PushError,
+}
+impl<U> CapacityError<U> {
+    fn new(value: U, kind: PushError) -> Self {
+        CapacityError { value, kind }
+    }
+    /// Reason for the failure
+    pub fn kind(&self) -> PushError {
+        self.kind
+    }
+    /// Obtain the value that couldn't be stored
+    pub fn into_inner(self) -> U {
+        self.value
+    }
+}
+impl<U> fmt::Display for CapacityError<U> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unable to store value: {}", self.kind)
+    }
+}
+#[cfg(feature = "std")]
+impl<U: fmt::Debug> ::std::error::Error for CapacityError<U> {}
+
+/// Maximum number of words of metadata in a fat pointer
+///
+/// Values with larger metadata (not possible with current rust) fail to be stored,
+/// e.g. `Value::new_stable` returns `Err` and `Stack::try_push_stable` returns `PushError::TooLarge`
+pub const MAX_METADATA_WORDS: usize = 4;
+
+/// Split a pointer into its address and metadata words
+///
+/// Returns `None` if the metadata is larger than `MAX_METADATA_WORDS`
+#[cfg(not(feature = "ptr_metadata"))]
+fn decompose_pointer<T: ?Sized>(
+    mut ptr: *const T,
+) -> Option<(*const (), usize, [usize; MAX_METADATA_WORDS])> {
     let addr = ptr as *const ();
     let rv = mem_as_slice(&mut ptr);
-    let mut vals = [0; 3];
     assert!(
         rv[0] == addr as usize,
         "BUG: Pointer layout is not (data_ptr, info...)"
     );
-    vals[..rv.len() - 1].copy_from_slice(&rv[1..]);
-    (addr, rv.len() - 1, vals)
+    copy_metadata(&rv[1..]).map(|(len, vals)| (addr, len, vals))
 }
 
+/// Split a pointer into its address and metadata words
+///
+/// Returns `None` if the metadata is larger than `MAX_METADATA_WORDS`, or isn't made of whole
+/// `usize` words
+#[cfg(feature = "ptr_metadata")]
+fn decompose_pointer<T: ?Sized>(
+    ptr: *const T,
+) -> Option<(*const (), usize, [usize; MAX_METADATA_WORDS])> {
+    let addr = ptr as *const ();
+    let meta = ptr::metadata(ptr);
+    let n_bytes = mem::size_of_val(&meta);
+    // Sized types have `()` metadata (which isn't word-aligned)
+    if n_bytes == 0 {
+        return Some((addr, 0, [0; MAX_METADATA_WORDS]));
+    }
+    if n_bytes % mem::size_of::<usize>() != 0
+        || mem::align_of_val(&meta) % mem::align_of::<usize>() != 0
+    {
+        return None;
+    }
+    // SAFE: Size and alignment checked above, and metadata is plain data
+    let words = unsafe {
+        core::slice::from_raw_parts(
+            &meta as *const _ as *const usize,
+            n_bytes / mem::size_of::<usize>(),
+        )
+    };
+    copy_metadata(words).map(|(len, vals)| (addr, len, vals))
+}
+
+/// Copy metadata words into a fixed-size array (failing if there are too many)
+fn copy_metadata(words: &[usize]) -> Option<(usize, [usize; MAX_METADATA_WORDS])> {
+    let mut vals = [0; MAX_METADATA_WORDS];
+    if words.len() > vals.len() {
+        return None;
+    }
+    vals[..words.len()].copy_from_slice(words);
+    Some((words.len(), vals))
+}
+
+#[cfg(not(feature = "ptr_metadata"))]
 fn mem_as_slice<T>(ptr: &mut T) -> &mut [usize] {
     assert!(mem::size_of::<T>() % mem::size_of::<usize>() == 0);
     assert!(mem::align_of::<T>() % mem::align_of::<usize>() == 0);
     let words = mem::size_of::<T>() / mem::size_of::<usize>();
     // SAFE: Points to valid memory (a raw pointer)
-    unsafe { slice::from_raw_parts_mut(ptr as *mut _ as *mut usize, words) }
+    unsafe { core::slice::from_raw_parts_mut(ptr as *mut _ as *mut usize, words) }
 }
 
 /// Re-construct a fat pointer
+#[cfg(feature = "ptr_metadata")]
+unsafe fn make_fat_ptr<T: ?Sized, W: Pod>(data_ptr: *mut (), meta_vals: &BufSlice<W>) -> *mut T {
+    let mut meta = MaybeUninit::<<T as ptr::Pointee>::Metadata>::uninit();
+    let n_bytes = mem::size_of_val(&meta);
+    assert!(n_bytes <= meta_vals.len() * mem::size_of::<W>());
+    ptr::copy_nonoverlapping(
+        meta_vals.as_ptr() as *const u8,
+        meta.as_mut_ptr() as *mut u8,
+        n_bytes,
+    );
+    ptr::from_raw_parts_mut(data_ptr, meta.assume_init())
+}
+/// Re-construct a fat pointer
+#[cfg(not(feature = "ptr_metadata"))]
 unsafe fn make_fat_ptr<T: ?Sized, W: Pod>(data_ptr: *mut (), meta_vals: &BufSlice<W>) -> *mut T {
     #[repr(C)]
     #[derive(Copy, Clone)]
     struct Raw {
         ptr: *const (),
-        meta: [usize; 4],
+        meta: [usize; MAX_METADATA_WORDS],
     }
     union Inner<T: ?Sized> {
         ptr: *mut T,
@@ -301,11 +865,12 @@ unsafe fn make_fat_ptr<T: ?Sized, W: Pod>(data_ptr: *mut (), meta_vals: &BufSlic
     let mut rv = Inner {
         raw: Raw {
             ptr: data_ptr,
-            meta: [0; 4],
+            meta: [0; MAX_METADATA_WORDS],
         },
     };
     assert!(meta_vals.len() * mem::size_of::<W>() % mem::size_of::<usize>() == 0);
-    assert!(meta_vals.len() * mem::size_of::<W>() <= 4 * mem::size_of::<usize>());
+    // Metadata is checked against `MAX_METADATA_WORDS` when stored
+    assert!(meta_vals.len() * mem::size_of::<W>() <= MAX_METADATA_WORDS * mem::size_of::<usize>());
     ptr::copy(
         meta_vals.as_ptr() as *const u8,
         rv.raw.meta.as_mut_ptr() as *mut u8,
@@ -334,6 +899,21 @@ fn store_metadata<W: Pod>(dst: &mut BufSlice<W>, meta_words: &[usize]) {
     }
 }
 
+/// Byte written over popped items in debug builds (see `poison_words`)
+const POISON_BYTE: u8 = 0xDE;
+
+/// Overwrite the words of a popped item with `POISON_BYTE` (debug builds only), so stale reads
+/// are obviously corrupt
+#[inline]
+fn poison_words<W>(words: &mut BufSlice<W>) {
+    if cfg!(debug_assertions) {
+        // SAFE: Writing to `MaybeUninit` words within the slice
+        unsafe {
+            ptr::write_bytes(words.as_mut_ptr(), POISON_BYTE, words.len());
+        }
+    }
+}
+
 fn round_to_words<T>(len: usize) -> usize {
     (len + mem::size_of::<T>() - 1) / mem::size_of::<T>()
 }
@@ -353,6 +933,25 @@ fn check_fat_pointer<U, T: ?Sized>(v: &U, get_ref: impl FnOnce(&U) -> &T) -> &T
     ptr
 }
 
+/// Mutable version of `check_fat_pointer`
+fn check_fat_pointer_mut<U, T: ?Sized>(
+    v: &mut U,
+    get_ref: impl FnOnce(&mut U) -> &mut T,
+) -> *const T {
+    let v_ptr = v as *const _ as *const u8;
+    let ptr: &mut T = get_ref(v);
+    assert_eq!(
+        ptr as *const _ as *const u8, v_ptr,
+        "MISUSE: Closure returned different pointer"
+    );
+    assert_eq!(
+        mem::size_of_val(ptr),
+        mem::size_of::<U>(),
+        "MISUSE: Closure returned a subset pointer"
+    );
+    ptr
+}
+
 /// Push items to a list using a generator function to get the items
 /// - `meta`  - Metadata slot (must be 1 usize long)
 /// - `data`  - Data slot, must be at least `count * sizeof(T)` long
@@ -405,7 +1004,46 @@ unsafe fn list_push_gen<T, W: Pod>(
     crate::store_metadata(meta, &[count]);
 }
 
+/// Extend a buffer, notifying it (via `DataBuf::on_extend`) if it grew
+fn extend_buf<D: DataBuf>(buf: &mut D, exact: bool, len: usize) -> Result<(), ()> {
+    let old = buf.as_ref().len();
+    if exact {
+        buf.extend_exact(len)?;
+    } else {
+        buf.extend(len)?;
+    }
+    let new = buf.as_ref().len();
+    if new > old {
+        buf.on_extend(old, new);
+    }
+    Ok(())
+}
+
+/// Check the alignment of a `U` stored at the start of `buf` (allowing for over-aligned buffers)
+fn check_value_alignment<U, D: DataBuf>(buf: &D)
+where
+    (U, D::Inner): AlignmentValid,
+{
+    if mem::align_of::<U>() > buf.data_alignment() {
+        <(U, D::Inner) as AlignmentValid>::check();
+    }
+}
+
+/// Non-panicking version of `check_value_alignment`
+fn value_alignment_fits<U, D: DataBuf>(buf: &D) -> bool {
+    mem::align_of::<U>() <= buf.data_alignment() || alignment_fits::<U, D::Inner>()
+}
+
+/// Non-panicking version of `AlignmentValid::check`
+fn alignment_fits<S, L>() -> bool {
+    mem::align_of::<S>() <= mem::align_of::<L>()
+}
+
 /// Marker trait used to check alignment
+///
+/// Without the `full_const_generics` feature this is checked at runtime, and a misaligned value
+/// causes a panic. The `try_*` methods (e.g. `Stack::try_push_stable`) instead return
+/// `PushError::Misaligned`. With `full_const_generics`, a misaligned value fails to compile.
 pub unsafe trait AlignmentValid {
     #[doc(hidden)]
     fn check();
@@ -429,9 +1067,58 @@ unsafe impl<S, L> AlignmentValid for (S, L) {
     }
 }
 
+/// Marker trait used to check that two types have the same size (and that the second type's alignment
+/// is no stricter than the first's)
+///
+/// Without the `full_const_generics` feature, this is checked at runtime (panicking on mismatch)
+pub unsafe trait LayoutMatches {
+    #[doc(hidden)]
+    fn check();
+}
+#[cfg(feature = "full_const_generics")]
+unsafe impl<S, L> LayoutMatches for (S, L)
+where
+    [(); mem::size_of::<L>() - mem::size_of::<S>()]: Sized,
+    [(); mem::size_of::<S>() - mem::size_of::<L>()]: Sized,
+    [(); mem::align_of::<S>() - mem::align_of::<L>()]: Sized,
+{
+    fn check() {}
+}
+#[cfg(not(feature = "full_const_generics"))]
+unsafe impl<S, L> LayoutMatches for (S, L) {
+    fn check() {
+        assert!(
+            mem::size_of::<S>() == mem::size_of::<L>(),
+            "transmute_contents: size_of::<U>() ({}) != size_of::<U2>() ({})",
+            mem::size_of::<S>(),
+            mem::size_of::<L>()
+        );
+        assert!(
+            mem::align_of::<L>() <= mem::align_of::<S>(),
+            "transmute_contents: align_of::<U2>() ({}) > align_of::<U>() ({})",
+            mem::align_of::<L>(),
+            mem::align_of::<S>()
+        );
+    }
+}
+
 /*
 #[cfg(doctest)]
 #[doc=include_str!("../README.md")]
 pub mod readme {
 }
 */
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    // Simulate a pointer with oversized metadata (no such pointers exist yet)
+    fn oversized_metadata() {
+        let words = [1, 2, 3, 4, 5];
+        assert_eq!(
+            super::copy_metadata(&words[..super::MAX_METADATA_WORDS]),
+            Some((super::MAX_METADATA_WORDS, [1, 2, 3, 4]))
+        );
+        assert_eq!(super::copy_metadata(&words), None);
+    }
+}
@@ -0,0 +1,44 @@
+//! Low-level fat pointer helpers, for building custom DST containers
+//!
+//! A fat pointer is treated as a data pointer followed by up to `MAX_METADATA_WORDS` words of
+//! metadata (e.g. the length of a slice, or the vtable of a trait object). The metadata words are
+//! opaque, and are only meaningful when re-assembled into a pointer of the same type.
+
+/// Split a (possibly fat) pointer into its data pointer and metadata words
+///
+/// Returns the data pointer, the metadata words (zero-padded), and the number of metadata words.
+/// Returns `None` if the metadata is larger than `MAX_METADATA_WORDS`.
+///
+/// ```
+/// let v = [1u8, 2, 3];
+/// let (data, meta, len) = ::stack_dst::raw::split_fat_ptr(&v[..] as *const [u8]).unwrap();
+/// assert_eq!(data, v.as_ptr() as *const ());
+/// assert_eq!(&meta[..len], &[3]);
+/// ```
+pub fn split_fat_ptr<T: ?Sized>(
+    ptr: *const T,
+) -> Option<(*const (), [usize; ::MAX_METADATA_WORDS], usize)> {
+    ::decompose_pointer(ptr).map(|(addr, len, meta)| (addr, meta, len))
+}
+
+/// Re-assemble a pointer from a data pointer and metadata words (see `split_fat_ptr`)
+///
+/// UNSAFE: `meta` must be the metadata words from splitting a `*const T` (e.g.
+/// `&meta[..len]` from `split_fat_ptr::<T>`). The returned pointer is only valid to dereference if
+/// `data` points to a valid `T` with that metadata.
+///
+/// ```
+/// let v = [1u8, 2, 3];
+/// let (data, meta, len) = ::stack_dst::raw::split_fat_ptr(&v[..] as *const [u8]).unwrap();
+/// let p: *const [u8] = unsafe { ::stack_dst::raw::assemble_fat_ptr(data, &meta[..len]) };
+/// assert_eq!(unsafe { &*p }, &[1, 2, 3]);
+/// ```
+pub unsafe fn assemble_fat_ptr<T: ?Sized>(data: *const (), meta: &[usize]) -> *const T {
+    assert!(
+        meta.len() <= ::MAX_METADATA_WORDS,
+        "Metadata is larger than `MAX_METADATA_WORDS`"
+    );
+    // `[usize]` and `[MaybeUninit<usize>]` have the same layout
+    let meta = &*(meta as *const [usize] as *const ::BufSlice<usize>);
+    ::make_fat_ptr::<T, usize>(data as *mut (), meta)
+}
@@ -1,7 +1,10 @@
 use core::{iter, marker, mem, ops, ptr};
 
+mod fixed;
 mod impls;
 
+pub use self::fixed::FixedStack;
+
 // Implementation Notes
 // -----
 //
@@ -20,14 +23,14 @@ pub struct Stack<T: ?Sized, D: ::DataBuf> {
     // Offset from the _back_ of `data` to the next free position.
     // I.e. data[data.len() - cur_ofs] is the first metadata word
     next_ofs: usize,
+    // Number of items currently in the stack
+    count: usize,
     data: D,
 }
 
 impl<T: ?Sized, D: ::DataBuf> ops::Drop for Stack<T, D> {
     fn drop(&mut self) {
-        while !self.is_empty() {
-            self.pop();
-        }
+        self.clear();
     }
 }
 impl<T: ?Sized, D: ::DataBuf + Default> Default for Stack<T, D> {
@@ -37,6 +40,9 @@ impl<T: ?Sized, D: ::DataBuf + Default> Default for Stack<T, D> {
 }
 
 impl<T: ?Sized, D: ::DataBuf> Stack<T, D> {
+    /// Size of this type in bytes (i.e. `mem::size_of::<Self>()`), for checking stack usage
+    pub const FOOTPRINT: usize = mem::size_of::<Self>();
+
     /// Construct a new (empty) stack
     pub fn new() -> Self
     where
@@ -49,15 +55,143 @@ impl<T: ?Sized, D: ::DataBuf> Stack<T, D> {
         Stack {
             _pd: marker::PhantomData,
             next_ofs: 0,
+            count: 0,
             data,
         }
     }
+    /// Check the internal offset is consistent (compiled out in release builds)
+    #[inline]
+    fn check_invariants(&self) {
+        debug_assert!(
+            self.next_ofs <= self.data.as_ref().len(),
+            "Stack invariant violated: next_ofs={} buffer_len={}",
+            self.next_ofs,
+            self.data.as_ref().len()
+        );
+    }
 
     /// Tests if the stack is empty
     pub fn is_empty(&self) -> bool {
+        self.check_invariants();
         self.next_ofs == 0
     }
 
+    /// Returns the number of items in the stack
+    ///
+    /// ```
+    /// let mut stack = ::stack_dst::Stack::<str, ::stack_dst::buffers::Ptr8>::new();
+    /// stack.push_str("Hello");
+    /// stack.push_str("world");
+    /// assert_eq!(stack.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.check_invariants();
+        self.count
+    }
+
+    /// Returns the size of the buffer, in words (of `D::Inner`)
+    pub fn capacity(&self) -> usize {
+        self.check_invariants();
+        self.data.as_ref().len()
+    }
+    /// Returns the size of the buffer, in bytes
+    pub fn capacity_bytes(&self) -> usize {
+        self.check_invariants();
+        self.data.as_ref().len() * mem::size_of::<D::Inner>()
+    }
+    /// Returns the number of bytes used by per-item metadata (e.g. vtable pointers or lengths) for
+    /// the current items
+    ///
+    /// ```
+    /// let mut list = ::stack_dst::Stack::<str, ::stack_dst::buffers::Ptr8>::new();
+    /// list.push_str("Hello");
+    /// list.push_str("World");
+    /// assert_eq!(list.overhead_bytes(), 2 * std::mem::size_of::<usize>());
+    /// ```
+    pub fn overhead_bytes(&self) -> usize {
+        self.check_invariants();
+        self.count * Self::meta_words() * mem::size_of::<D::Inner>()
+    }
+
+    /// Returns the number of `U` values that can be pushed without growing the buffer
+    ///
+    /// ```
+    /// # use std::any::Any;
+    /// let mut stack = ::stack_dst::Stack::<dyn Any, ::stack_dst::buffers::Ptr8>::new();
+    /// // Each item takes one word for the value, and one for the vtable
+    /// assert_eq!(stack.remaining_capacity_for::<usize>(), 4);
+    /// ```
+    pub fn remaining_capacity_for<U>(&self) -> usize {
+        self.check_invariants();
+        let words = Self::meta_words() + D::round_to_words(mem::size_of::<U>());
+        if words == 0 {
+            return usize::MAX;
+        }
+        (self.data.as_ref().len() - self.next_ofs) / words
+    }
+
+    /// Ensure that at least `words` words (of `D::Inner`) are free, growing the buffer if needed
+    ///
+    /// The buffer may over-allocate (see `DataBuf::extend`), use `reserve_exact` to avoid this.
+    /// Fixed-size buffers can't grow, so this only succeeds if there is already enough space.
+    ///
+    /// ```
+    /// # #[cfg(feature = "alloc")] {
+    /// let mut stack = ::stack_dst::Stack::<str, ::stack_dst::buffers::PtrVec>::new();
+    /// stack.reserve(10).unwrap();
+    /// assert!(stack.capacity() >= 10);
+    /// # }
+    /// ```
+    pub fn reserve(&mut self, words: usize) -> Result<(), ()> {
+        self.check_invariants();
+        self.grow(words, false)
+    }
+
+    /// Ensure that at least `words` words (of `D::Inner`) are free, without over-allocating
+    ///
+    /// ```
+    /// # #[cfg(feature = "alloc")] {
+    /// let mut stack = ::stack_dst::Stack::<str, ::stack_dst::buffers::PtrVec>::new();
+    /// stack.reserve_exact(10).unwrap();
+    /// assert_eq!(stack.capacity(), 10);
+    /// # }
+    /// ```
+    pub fn reserve_exact(&mut self, words: usize) -> Result<(), ()> {
+        self.check_invariants();
+        self.grow(words, true)
+    }
+
+    /// Ensure that `n` more items of type `U` can be pushed without growing the buffer again
+    ///
+    /// Each item needs `round_to_words(size_of::<U>())` words for the data, plus the metadata.
+    ///
+    /// ```
+    /// # #[cfg(feature = "alloc")] {
+    /// # use std::any::Any;
+    /// let mut stack = ::stack_dst::Stack::<dyn Any, ::stack_dst::buffers::PtrVec>::new();
+    /// stack.reserve_items::<u64>(10).unwrap();
+    /// assert!(stack.remaining_capacity_for::<u64>() >= 10);
+    /// # }
+    /// ```
+    pub fn reserve_items<U>(&mut self, n: usize) -> Result<(), ()> {
+        self.check_invariants();
+        let words = Self::meta_words() + D::round_to_words(mem::size_of::<U>());
+        let total = words.checked_mul(n).ok_or(())?;
+        self.grow(total, false)
+    }
+
+    fn grow(&mut self, words: usize, exact: bool) -> Result<(), ()> {
+        let req_space = self.next_ofs.checked_add(words).ok_or(())?;
+        let old_len = self.data.as_ref().len();
+        if req_space > old_len {
+            crate::extend_buf(&mut self.data, exact, req_space)?;
+            // Items are stored at the back of the buffer, so move them to the new back
+            let new_len = self.data.as_ref().len();
+            self.data.as_mut().rotate_right(new_len - old_len);
+        }
+        Ok(())
+    }
+
     fn meta_words() -> usize {
         D::round_to_words(mem::size_of::<&T>() - mem::size_of::<usize>())
     }
@@ -88,16 +222,55 @@ impl<T: ?Sized, D: ::DataBuf> Stack<T, D> {
     where
         (U, D::Inner): crate::AlignmentValid,
     {
+        self.check_invariants();
         <(U, D::Inner) as crate::AlignmentValid>::check();
+        self.try_push_stable_inner(v, f).map_err(|e| e.into_inner())
+    }
+
+    /// Push a value at the top of the stack, reporting why the push failed
+    ///
+    /// Unlike `push_stable`, this returns `PushError::Misaligned` instead of panicking if `U` is
+    /// more strictly aligned than the buffer.
+    ///
+    /// ```
+    /// # use stack_dst::{Stack, PushError};
+    /// let mut stack = Stack::<[u8], ::stack_dst::buffers::Ptr2>::new();
+    /// assert_eq!(stack.try_push_stable([0u8; 64], |v| v).unwrap_err().kind(), PushError::TooLarge);
+    /// stack.try_push_stable([1], |v| v).unwrap();
+    /// assert_eq!(stack.try_push_stable([2], |v| v).unwrap_err().kind(), PushError::Full);
+    /// ```
+    pub fn try_push_stable<U, F: FnOnce(&U) -> &T>(
+        &mut self,
+        v: U,
+        f: F,
+    ) -> Result<(), crate::CapacityError<U>>
+    where
+        (U, D::Inner): crate::AlignmentValid,
+    {
+        self.check_invariants();
+        self.try_push_stable_inner(v, f)
+    }
+    fn try_push_stable_inner<U, F: FnOnce(&U) -> &T>(
+        &mut self,
+        v: U,
+        f: F,
+    ) -> Result<(), crate::CapacityError<U>>
+    where
+        (U, D::Inner): crate::AlignmentValid,
+    {
+        if !crate::alignment_fits::<U, D::Inner>() {
+            return Err(crate::CapacityError::new(v, crate::PushError::Misaligned));
+        }
 
         // SAFE: Destination address is valid
         unsafe {
             match self.push_inner(crate::check_fat_pointer(&v, f)) {
                 Ok(pii) => {
                     ptr::write(pii.data.as_mut_ptr() as *mut U, v);
+                    self.count += 1;
                     Ok(())
                 }
-                Err(_) => Err(v),
+                Err(e) => Err(crate::CapacityError::new(v, e)),
             }
         }
     }
@@ -137,14 +310,62 @@ impl<T: ?Sized, D: ::DataBuf> Stack<T, D> {
     }
     /// Returns a pointer to the top item on the stack
     pub fn top(&self) -> Option<&T> {
+        self.check_invariants();
         self.top_raw().map(|x| unsafe { &*x })
     }
     /// Returns a pointer to the top item on the stack (unique/mutable)
     pub fn top_mut(&mut self) -> Option<&mut T> {
+        self.check_invariants();
         self.top_raw_mut().map(|x| unsafe { &mut *x })
     }
+    /// Returns the top item, first pushing the result of `make` if the stack is empty
+    ///
+    /// Returns `Err` with the new value if it couldn't be pushed
+    ///
+    /// ```
+    /// # use std::fmt::Display;
+    /// let mut stack = ::stack_dst::Stack::<dyn Display, ::stack_dst::buffers::Ptr8>::new();
+    /// assert_eq!(stack.top_or_push(|| 1, |v| v).ok().unwrap().to_string(), "1");
+    /// assert_eq!(stack.top_or_push(|| 2, |v| v).ok().unwrap().to_string(), "1");
+    /// ```
+    pub fn top_or_push<U, F, G>(&mut self, make: F, get_ref: G) -> Result<&mut T, U>
+    where
+        F: FnOnce() -> U,
+        G: FnOnce(&U) -> &T,
+        (U, D::Inner): crate::AlignmentValid,
+    {
+        self.check_invariants();
+        if self.next_ofs == 0 {
+            <(U, D::Inner) as crate::AlignmentValid>::check();
+            self.try_push_stable_inner(make(), get_ref)
+                .map_err(|e| e.into_inner())?;
+        }
+        let ptr = self.top_raw_mut().expect("BUG: Empty after push");
+        // SAFE: Pointer is valid, and borrows `self`
+        Ok(unsafe { &mut *ptr })
+    }
+    /// Remove all items from the stack (dropping them)
+    ///
+    /// ```
+    /// let mut stack = ::stack_dst::Stack::<str, ::stack_dst::buffers::Ptr8>::new();
+    /// stack.push_str("Hello");
+    /// stack.clear();
+    /// assert!(stack.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.check_invariants();
+        while self.next_ofs != 0 {
+            self.pop_inner();
+        }
+    }
     /// Pop the top item off the stack
+    ///
+    /// In debug builds, the freed words are overwritten with `0xDE` bytes.
     pub fn pop(&mut self) {
+        self.check_invariants();
+        self.pop_inner()
+    }
+    fn pop_inner(&mut self) {
         if let Some(ptr) = self.top_raw_mut() {
             assert!(self.next_ofs > 0);
             // SAFE: Pointer is valid, and will never be accessed after this point
@@ -153,10 +374,123 @@ impl<T: ?Sized, D: ::DataBuf> Stack<T, D> {
                 ptr::drop_in_place(ptr);
                 D::round_to_words(size)
             };
+            let old_ofs = self.next_ofs;
             self.next_ofs -= words + Self::meta_words();
+            self.count -= 1;
+            let len = self.data.as_ref().len();
+            crate::poison_words(&mut self.data.as_mut()[len - old_ofs..len - self.next_ofs]);
         }
     }
 
+    /// Pop up to `n` items off the stack, returning the number removed
+    ///
+    /// ```
+    /// let mut stack = ::stack_dst::Stack::<str, ::stack_dst::buffers::Ptr8>::new();
+    /// stack.push_str("a").unwrap();
+    /// stack.push_str("b").unwrap();
+    /// stack.push_str("c").unwrap();
+    /// assert_eq!(stack.pop_n(2), 2);
+    /// assert_eq!(stack.top(), Some("a"));
+    /// assert_eq!(stack.pop_n(5), 1);
+    /// ```
+    pub fn pop_n(&mut self, n: usize) -> usize {
+        self.check_invariants();
+        let n = ::core::cmp::min(n, self.count);
+        for _ in 0..n {
+            self.pop_inner();
+        }
+        n
+    }
+
+    /// Convert a stack holding exactly one item into a `Value`, reusing the buffer
+    ///
+    /// Returns the stack unchanged if it doesn't hold exactly one item.
+    ///
+    /// ```
+    /// let mut stack = ::stack_dst::Stack::<str, ::stack_dst::buffers::Ptr8>::new();
+    /// stack.push_str("Hello").unwrap();
+    /// let v = stack.into_single().ok().unwrap();
+    /// assert_eq!(&v[..], "Hello");
+    /// ```
+    pub fn into_single(self) -> Result<::Value<T, D>, Self> {
+        self.check_invariants();
+        if self.count != 1 {
+            return Err(self);
+        }
+        let mut this = mem::ManuallyDrop::new(self);
+        // SAFE: The stack holds an item at `next_ofs`, and the stack is forgotten (without dropping
+        // the item) once its buffer is taken
+        unsafe {
+            let ofs = this.next_ofs;
+            let item = this.raw_at_mut(ofs);
+            Ok(::Value::take_from_buffer(&mut this.data, item))
+        }
+    }
+
+    /// Remove any items that don't meet a predicate
+    ///
+    /// The predicate is called on items in the order they would be popped (top first)
+    ///
+    /// ```
+    /// let mut list = ::stack_dst::Stack::<str, ::stack_dst::buffers::Ptr8>::new();
+    /// list.push_str("Hello");
+    /// list.push_str("cruel");
+    /// list.push_str("world");
+    /// list.retain(|v| v != "cruel");
+    /// let mut it = list.iter();
+    /// assert_eq!(it.next(), Some("world"));
+    /// assert_eq!(it.next(), Some("Hello"));
+    /// assert_eq!(it.next(), None);
+    /// ```
+    pub fn retain<Cb>(&mut self, mut cb: Cb)
+    where
+        Cb: FnMut(&mut T) -> bool,
+    {
+        self.check_invariants();
+        let len = self.data.as_ref().len();
+        let base = len - self.next_ofs;
+        // Leak all items if `cb` panics
+        self.next_ofs = 0;
+        self.count = 0;
+        let mut ofs = base;
+        let mut writeback_pos = base;
+        let mut kept = 0;
+        // Walk from the top down, packing retained items towards the top
+        while ofs < len {
+            let v: &mut T = unsafe {
+                let meta = &mut self.data.as_mut()[ofs..];
+                let mw = Self::meta_words();
+                let (meta, data) = meta.split_at_mut(mw);
+                &mut *super::make_fat_ptr(data.as_mut_ptr() as *mut (), meta)
+            };
+            let words = Self::meta_words() + D::round_to_words(mem::size_of_val(v));
+            if cb(v) {
+                if writeback_pos != ofs {
+                    let d = self.data.as_mut();
+                    // writeback is always before `ofs`, so this ordering is correct
+                    for i in 0..words {
+                        let (a, b) = d.split_at_mut(ofs + i);
+                        a[writeback_pos + i] = b[0];
+                    }
+                }
+                writeback_pos += words;
+                kept += 1;
+            } else {
+                // Don't update `writeback_pos`
+                // SAFE: Valid pointer, won't be accessed again
+                unsafe {
+                    ptr::drop_in_place(v);
+                }
+            }
+            ofs += words;
+        }
+        assert!(ofs == len);
+        // Retained items are now packed at the start of the used region, move them to the back
+        self.data.as_mut()[base..].rotate_right(len - writeback_pos);
+        self.next_ofs = writeback_pos - base;
+        self.count = kept;
+    }
+
     /// Obtain an immutable iterator (yields references to items, in the order they would be popped)
     /// ```
     /// let mut list = ::stack_dst::Stack::<str, ::stack_dst::buffers::Ptr8>::new();
@@ -167,8 +501,9 @@ impl<T: ?Sized, D: ::DataBuf> Stack<T, D> {
     /// assert_eq!(it.next(), Some("Hello"));
     /// assert_eq!(it.next(), None);
     /// ```
-    pub fn iter(&self) -> Iter<T, D> {
-        Iter(self, self.next_ofs)
+    pub fn iter(&self) -> Iter<'_, T, D> {
+        self.check_invariants();
+        Iter(self, self.next_ofs, self.count)
     }
     /// Obtain unique/mutable iterator
     /// ```
@@ -183,8 +518,95 @@ impl<T: ?Sized, D: ::DataBuf> Stack<T, D> {
     /// assert_eq!(it.next(), Some(&[0,2,3][..]));
     /// assert_eq!(it.next(), None);
     /// ```
-    pub fn iter_mut(&mut self) -> IterMut<T, D> {
-        IterMut(self, self.next_ofs)
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, D> {
+        self.check_invariants();
+        IterMut(self, self.next_ofs, self.count)
+    }
+
+    /// Returns the index (in iteration order, i.e. top first) of the first item matching `f`
+    ///
+    /// ```
+    /// let mut list = ::stack_dst::Stack::<str, ::stack_dst::buffers::Ptr8>::new();
+    /// list.push_str("a");
+    /// list.push_str("bb");
+    /// assert_eq!(list.position(|v| v.len() == 2), Some(0));
+    /// assert_eq!(list.position(|v| v.is_empty()), None);
+    /// ```
+    pub fn position<F: FnMut(&T) -> bool>(&self, f: F) -> Option<usize> {
+        self.iter().position(f)
+    }
+    /// Returns the first item (in iteration order) matching `f`
+    pub fn find<F: FnMut(&T) -> bool>(&self, mut f: F) -> Option<&T> {
+        self.iter().find(|v| f(v))
+    }
+
+    /// Call `f` on each item (top first)
+    ///
+    /// Equivalent to `iter().for_each(f)`, but without the iterator's per-item state
+    /// ```
+    /// let mut list = ::stack_dst::Stack::<dyn Fn() -> i32, ::stack_dst::buffers::Ptr8>::new();
+    /// list.push_stable(|| 1, |p| p).ok().unwrap();
+    /// list.push_stable(|| 2, |p| p).ok().unwrap();
+    /// let mut values = Vec::new();
+    /// list.for_each(|f| values.push(f()));
+    /// assert_eq!(values, [2, 1]);
+    /// ```
+    pub fn for_each<F: FnMut(&T)>(&self, mut f: F) {
+        self.check_invariants();
+        let mut ofs = self.next_ofs;
+        while ofs != 0 {
+            // SAFE: Bounds checked, and the metadata is valid by internal consistency
+            let v = unsafe { &*self.raw_at(ofs) };
+            ofs -= Self::meta_words() + D::round_to_words(mem::size_of_val(v));
+            f(v);
+        }
+    }
+    /// Call `f` on each item (top first) with a mutable reference
+    pub fn for_each_mut<F: FnMut(&mut T)>(&mut self, mut f: F) {
+        self.check_invariants();
+        let mut ofs = self.next_ofs;
+        while ofs != 0 {
+            // SAFE: Bounds checked, and only one item is borrowed at a time
+            let v = unsafe { &mut *self.raw_at_mut(ofs) };
+            ofs -= Self::meta_words() + D::round_to_words(mem::size_of_val(v));
+            f(v);
+        }
+    }
+}
+
+impl<T: ?Sized, D: ::DataBuf> Stack<T, D> {
+    /// Clear the stack, then fill it with clones of `source`'s items
+    ///
+    /// Items are placed at the same offsets as in `source`, so the buffer is only extended if it is
+    /// smaller than the space used by `source`.
+    ///
+    /// UNSAFE: `clone_item` must fully initialise the destination (which has the same metadata as
+    /// the source item)
+    unsafe fn clone_from_with<F>(&mut self, source: &Self, mut clone_item: F)
+    where
+        F: FnMut(*mut T, &T),
+    {
+        self.clear();
+        let used = source.next_ofs;
+        if self.data.as_ref().len() < used {
+            crate::extend_buf(&mut self.data, false, used)
+                .expect("Stack::clone_from - Unable to extend buffer");
+        }
+        // Clone from the top down, only marking the items as present at the end (leaking if
+        // `clone_item` panics)
+        let mw = Self::meta_words();
+        let mut ofs = used;
+        while ofs > 0 {
+            let src_len = source.data.as_ref().len();
+            let dst_len = self.data.as_ref().len();
+            self.data.as_mut()[dst_len - ofs..][..mw]
+                .copy_from_slice(&source.data.as_ref()[src_len - ofs..][..mw]);
+            let src = &*source.raw_at(ofs);
+            clone_item(self.raw_at_mut(ofs), src);
+            ofs -= mw + D::round_to_words(mem::size_of_val(src));
+        }
+        self.next_ofs = used;
+        self.count = source.count;
     }
 }
 
@@ -199,9 +621,13 @@ struct PushInnerInfo<'a, DInner> {
 }
 impl<T: ?Sized, D: ::DataBuf> Stack<T, D> {
     /// See `push_inner_raw`
-    unsafe fn push_inner(&mut self, fat_ptr: &T) -> Result<PushInnerInfo<D::Inner>, ()> {
+    unsafe fn push_inner(
+        &mut self,
+        fat_ptr: &T,
+    ) -> Result<PushInnerInfo<'_, D::Inner>, crate::PushError> {
         let bytes = mem::size_of_val(fat_ptr);
-        let (_data_ptr, len, v) = crate::decompose_pointer(fat_ptr);
+        let (_data_ptr, len, v) =
+            crate::decompose_pointer(fat_ptr).ok_or(crate::PushError::TooLarge)?;
         self.push_inner_raw(bytes, &v[..len])
     }
 
@@ -213,7 +639,7 @@ impl<T: ?Sized, D: ::DataBuf> Stack<T, D> {
         &mut self,
         bytes: usize,
         metadata: &[usize],
-    ) -> Result<PushInnerInfo<D::Inner>, ()> {
+    ) -> Result<PushInnerInfo<'_, D::Inner>, crate::PushError> {
         assert!(D::round_to_words(mem::size_of_val(metadata)) == Self::meta_words());
         let words = D::round_to_words(bytes) + Self::meta_words();
 
@@ -221,7 +647,7 @@ impl<T: ?Sized, D: ::DataBuf> Stack<T, D> {
         // Attempt resize (if the underlying buffer allows it)
         if req_space > self.data.as_ref().len() {
             let old_len = self.data.as_ref().len();
-            if let Ok(_) = self.data.extend(req_space) {
+            if let Ok(_) = crate::extend_buf(&mut self.data, false, req_space) {
                 let new_len = self.data.as_ref().len();
                 self.data.as_mut().rotate_right(new_len - old_len);
             }
@@ -247,12 +673,43 @@ impl<T: ?Sized, D: ::DataBuf> Stack<T, D> {
                 reset_value: prev_next_ofs,
             })
         } else {
-            Err(())
+            Err(crate::PushError::classify::<D>(words, req_space))
         }
     }
 }
 
 impl<D: ::DataBuf> Stack<str, D> {
+    /// Copy all items into a `Vec` of owned strings (top first, i.e. in iteration order)
+    ///
+    /// ```
+    /// # use stack_dst::Stack;
+    /// let stack = Stack::<str, ::stack_dst::buffers::Ptr8>::from_strs(&["a", "b"]).unwrap();
+    /// assert_eq!(stack.collect_cloned(), ["b", "a"]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn collect_cloned(&self) -> ::alloc::vec::Vec<::alloc::string::String> {
+        self.iter().map(::alloc::string::String::from).collect()
+    }
+
+    /// Construct a stack from a sequence of strings (pushed in order, so the last is on top)
+    ///
+    /// ```
+    /// # use stack_dst::Stack;
+    /// let stack = Stack::<str, ::stack_dst::buffers::Ptr16>::from_strs(["a", "b", "c"].iter()).unwrap();
+    /// assert_eq!(stack.iter().collect::<Vec<_>>(), ["c", "b", "a"]);
+    /// ```
+    pub fn from_strs<I>(it: I) -> Result<Self, ()>
+    where
+        D: Default,
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut rv = Self::new();
+        for v in it {
+            rv.push_str(v.as_ref())?;
+        }
+        Ok(rv)
+    }
     /// Push the contents of a string slice as an item onto the stack
     ///
     /// ```
@@ -261,6 +718,18 @@ impl<D: ::DataBuf> Stack<str, D> {
     /// stack.push_str("Hello!");
     /// ```
     pub fn push_str(&mut self, v: &str) -> Result<(), ()> {
+        self.try_push_str(v).map_err(|_| ())
+    }
+    /// Push the contents of a string slice, reporting why the push failed
+    ///
+    /// ```
+    /// # use stack_dst::{Stack, PushError};
+    /// let mut stack = Stack::<str, ::stack_dst::buffers::U8_32>::new();
+    /// stack.try_push_str("Hello!").unwrap();
+    /// assert_eq!(stack.try_push_str(&"a".repeat(64)), Err(PushError::TooLarge));
+    /// ```
+    pub fn try_push_str(&mut self, v: &str) -> Result<(), crate::PushError> {
+        self.check_invariants();
         unsafe {
             self.push_inner(v).map(|pii| {
                 ptr::copy(
@@ -268,14 +737,47 @@ impl<D: ::DataBuf> Stack<str, D> {
                     pii.data.as_mut_ptr() as *mut u8,
                     v.len(),
                 )
-            })
+            })?;
         }
+        self.count += 1;
+        Ok(())
     }
 }
 impl<D: ::DataBuf, T: Clone> Stack<[T], D>
 where
     (T, D::Inner): crate::AlignmentValid,
 {
+    /// Clone all items into a `Vec` of `Vec`s (top first, i.e. in iteration order)
+    ///
+    /// ```
+    /// # use stack_dst::Stack;
+    /// let stack = Stack::<[u8], ::stack_dst::buffers::Ptr8>::from_slices(&[&[1, 2][..], &[3]]).unwrap();
+    /// assert_eq!(stack.collect_cloned(), [vec![3], vec![1, 2]]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn collect_cloned(&self) -> ::alloc::vec::Vec<::alloc::vec::Vec<T>> {
+        self.iter().map(|v| v.to_vec()).collect()
+    }
+
+    /// Construct a stack from a sequence of slices (cloning the items, pushed in order)
+    ///
+    /// ```
+    /// # use stack_dst::Stack;
+    /// let stack = Stack::<[u8], ::stack_dst::buffers::Ptr8>::from_slices(vec![vec![1, 2], vec![3]]).unwrap();
+    /// assert_eq!(stack.top(), Some(&[3][..]));
+    /// ```
+    pub fn from_slices<I>(it: I) -> Result<Self, ()>
+    where
+        D: Default,
+        I: IntoIterator,
+        I::Item: AsRef<[T]>,
+    {
+        let mut rv = Self::new();
+        for v in it {
+            rv.push_cloned(v.as_ref())?;
+        }
+        Ok(rv)
+    }
     /// Pushes a set of items (cloning out of the input slice)
     ///
     /// ```
@@ -298,17 +800,22 @@ where
     where
         T: Copy,
     {
+        self.check_invariants();
         <(T, D::Inner) as crate::AlignmentValid>::check();
         // SAFE: Carefully constructed to maintain consistency
         unsafe {
-            self.push_inner(v).map(|pii| {
-                ptr::copy(
-                    v.as_ptr() as *const u8,
-                    pii.data.as_mut_ptr() as *mut u8,
-                    mem::size_of_val(v),
-                )
-            })
+            self.push_inner(v)
+                .map(|pii| {
+                    ptr::copy(
+                        v.as_ptr() as *const u8,
+                        pii.data.as_mut_ptr() as *mut u8,
+                        mem::size_of_val(v),
+                    )
+                })
+                .map_err(|_| ())?;
         }
+        self.count += 1;
+        Ok(())
     }
 }
 impl<D: crate::DataBuf, T> Stack<[T], D>
@@ -326,10 +833,13 @@ where
     /// assert_eq!(stack.top().unwrap(), &[0,1,2,3,4,5,6,7,8,9]);
     /// ```
     pub fn push_from_iter(&mut self, mut iter: impl ExactSizeIterator<Item = T>) -> Result<(), ()> {
+        self.check_invariants();
         <(T, D::Inner) as crate::AlignmentValid>::check();
         // SAFE: API used correctly
         unsafe {
-            let pii = self.push_inner_raw(iter.len() * mem::size_of::<T>(), &[0])?;
+            let pii = self
+                .push_inner_raw(iter.len() * mem::size_of::<T>(), &[0])
+                .map_err(|_| ())?;
             crate::list_push_gen(
                 pii.meta,
                 pii.data,
@@ -338,13 +848,61 @@ where
                 pii.reset_slot,
                 pii.reset_value,
             );
-            Ok(())
+        }
+        self.count += 1;
+        Ok(())
+    }
+}
+
+impl<D: ::DataBuf + Default> Clone for Stack<str, D> {
+    fn clone(&self) -> Self {
+        let mut rv = Self::new();
+        rv.clone_from(self);
+        rv
+    }
+    /// Clone the items from `source`, reusing the existing buffer
+    ///
+    /// ```
+    /// # use stack_dst::Stack;
+    /// let mut stack = Stack::<str, ::stack_dst::buffers::Ptr8>::new();
+    /// stack.push_str("Hello");
+    /// let mut other = Stack::new();
+    /// other.push_str("Goodbye");
+    /// other.clone_from(&stack);
+    /// assert_eq!(other.top(), Some("Hello"));
+    /// assert_eq!(other.len(), 1);
+    /// ```
+    fn clone_from(&mut self, source: &Self) {
+        // SAFE: The destination has the same length as the source
+        unsafe {
+            self.clone_from_with(source, |dst, src| {
+                ptr::copy_nonoverlapping(src.as_ptr(), dst as *mut u8, src.len())
+            })
+        }
+    }
+}
+impl<D: ::DataBuf + Default, T: Clone> Clone for Stack<[T], D> {
+    fn clone(&self) -> Self {
+        let mut rv = Self::new();
+        rv.clone_from(self);
+        rv
+    }
+    /// Clone the items from `source`, reusing the existing buffer
+    fn clone_from(&mut self, source: &Self) {
+        // SAFE: The destination has the same length as the source, and all items are written
+        unsafe {
+            self.clone_from_with(source, |dst, src| {
+                let dst = dst as *mut T;
+                for (i, v) in src.iter().enumerate() {
+                    ptr::write(dst.add(i), v.clone());
+                }
+            })
         }
     }
 }
 
 /// DST Stack iterator (immutable)
-pub struct Iter<'a, T: 'a + ?Sized, D: 'a + crate::DataBuf>(&'a Stack<T, D>, usize);
+pub struct Iter<'a, T: 'a + ?Sized, D: 'a + crate::DataBuf>(&'a Stack<T, D>, usize, usize);
 impl<'a, T: 'a + ?Sized, D: 'a + crate::DataBuf> iter::Iterator for Iter<'a, T, D> {
     type Item = &'a T;
     fn next(&mut self) -> Option<&'a T> {
@@ -354,13 +912,18 @@ impl<'a, T: 'a + ?Sized, D: 'a + crate::DataBuf> iter::Iterator for Iter<'a, T,
             // SAFE: Bounds checked, aliasing enforced by API
             let rv = unsafe { &*self.0.raw_at(self.1) };
             self.1 -= Stack::<T, D>::meta_words() + D::round_to_words(mem::size_of_val(rv));
+            self.2 -= 1;
             Some(rv)
         }
     }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.2, Some(self.2))
+    }
 }
+impl<'a, T: 'a + ?Sized, D: 'a + crate::DataBuf> iter::ExactSizeIterator for Iter<'a, T, D> {}
 
 /// DST Stack iterator (immutable)
-pub struct IterMut<'a, T: 'a + ?Sized, D: 'a + crate::DataBuf>(&'a mut Stack<T, D>, usize);
+pub struct IterMut<'a, T: 'a + ?Sized, D: 'a + crate::DataBuf>(&'a mut Stack<T, D>, usize, usize);
 impl<'a, T: 'a + ?Sized, D: 'a + crate::DataBuf> iter::Iterator for IterMut<'a, T, D> {
     type Item = &'a mut T;
     fn next(&mut self) -> Option<&'a mut T> {
@@ -370,7 +933,12 @@ impl<'a, T: 'a + ?Sized, D: 'a + crate::DataBuf> iter::Iterator for IterMut<'a,
             // SAFE: Bounds checked, aliasing enforced by API
             let rv = unsafe { &mut *self.0.raw_at_mut(self.1) };
             self.1 -= Stack::<T, D>::meta_words() + D::round_to_words(mem::size_of_val(rv));
+            self.2 -= 1;
             Some(rv)
         }
     }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.2, Some(self.2))
+    }
 }
+impl<'a, T: 'a + ?Sized, D: 'a + crate::DataBuf> iter::ExactSizeIterator for IterMut<'a, T, D> {}
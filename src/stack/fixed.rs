@@ -0,0 +1,159 @@
+use core::mem;
+
+/// A `Stack` where every item has the same size, allowing O(1) indexing
+///
+/// The item size is set by the first push (and released once the stack is empty again), pushing an
+/// item of any other size fails with `PushError::SizeMismatch`. Useful for a set of trait objects
+/// that are all the same concrete type (or same-sized types).
+///
+/// ```
+/// # use stack_dst::{FixedStack, PushError};
+/// # use std::fmt::Display;
+/// let mut stack = FixedStack::<dyn Display, ::stack_dst::buffers::Ptr8>::new();
+/// stack.push_stable(1u32, |v| v as _).unwrap();
+/// stack.push_stable('x', |v| v as _).unwrap();
+/// let e = stack.try_push_stable(2u64, |v| v as _).unwrap_err();
+/// assert_eq!(e.kind(), PushError::SizeMismatch);
+/// assert_eq!(stack.get(0).unwrap().to_string(), "x");
+/// assert_eq!(stack.get(1).unwrap().to_string(), "1");
+/// ```
+pub struct FixedStack<T: ?Sized, D: ::DataBuf> {
+    inner: super::Stack<T, D>,
+    // Size in bytes of every item, only meaningful when `inner` is non-empty
+    item_size: usize,
+}
+
+impl<T: ?Sized, D: ::DataBuf + Default> Default for FixedStack<T, D> {
+    fn default() -> Self {
+        FixedStack::new()
+    }
+}
+
+impl<T: ?Sized, D: ::DataBuf> FixedStack<T, D> {
+    /// Construct a new (empty) stack
+    pub fn new() -> Self
+    where
+        D: Default,
+    {
+        Self::with_buffer(D::default())
+    }
+    /// Construct a new (empty) stack using the provided buffer
+    pub fn with_buffer(data: D) -> Self {
+        FixedStack {
+            inner: super::Stack::with_buffer(data),
+            item_size: 0,
+        }
+    }
+
+    /// Tests if the stack is empty
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+    /// Returns the number of items in the stack
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+    /// Size in bytes of the stored items (`None` if the stack is empty)
+    pub fn item_size(&self) -> Option<usize> {
+        if self.inner.is_empty() {
+            None
+        } else {
+            Some(self.item_size)
+        }
+    }
+
+    /// Number of words used by each item (data and metadata)
+    fn stride(&self) -> usize {
+        D::round_to_words(self.item_size) + super::Stack::<T, D>::meta_words()
+    }
+
+    /// Push a value at the top of the stack
+    #[cfg(feature = "unsize")]
+    pub fn push<U: ::core::marker::Unsize<T>>(&mut self, v: U) -> Result<(), U>
+    where
+        (U, D::Inner): crate::AlignmentValid,
+    {
+        self.push_stable(v, |p| p)
+    }
+    /// Push a value at the top of the stack (without using `Unsize`)
+    ///
+    /// Returns `Err` with the value if it doesn't fit, or if its size differs from the other items
+    pub fn push_stable<U, F: FnOnce(&U) -> &T>(&mut self, v: U, f: F) -> Result<(), U>
+    where
+        (U, D::Inner): crate::AlignmentValid,
+    {
+        <(U, D::Inner) as crate::AlignmentValid>::check();
+        self.try_push_stable(v, f).map_err(|e| e.into_inner())
+    }
+    /// Push a value at the top of the stack, reporting why the push failed
+    pub fn try_push_stable<U, F: FnOnce(&U) -> &T>(
+        &mut self,
+        v: U,
+        f: F,
+    ) -> Result<(), crate::CapacityError<U>>
+    where
+        (U, D::Inner): crate::AlignmentValid,
+    {
+        // NOTE: `check_fat_pointer` (used by the push) ensures that the stored size is the size of `U`
+        let size = mem::size_of::<U>();
+        if !self.inner.is_empty() && size != self.item_size {
+            return Err(crate::CapacityError::new(v, crate::PushError::SizeMismatch));
+        }
+        self.inner.try_push_stable(v, f)?;
+        self.item_size = size;
+        Ok(())
+    }
+
+    /// Returns the item at `index` (in iteration order, i.e. top first), in constant time
+    ///
+    /// ```
+    /// let mut stack = ::stack_dst::FixedStack::<[u8], ::stack_dst::buffers::Ptr8>::new();
+    /// stack.push_stable([1, 2], |v| v).unwrap();
+    /// stack.push_stable([3, 4], |v| v).unwrap();
+    /// assert_eq!(stack.get(1), Some(&[1, 2][..]));
+    /// assert_eq!(stack.get(2), None);
+    /// ```
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.inner.len() {
+            let ofs = self.inner.next_ofs - index * self.stride();
+            // SAFE: All items are `stride` words long, so `ofs` is the start of an item
+            Some(unsafe { &*self.inner.raw_at(ofs) })
+        } else {
+            None
+        }
+    }
+    /// Returns the item at `index` (in iteration order, i.e. top first) mutably, in constant time
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.inner.len() {
+            let ofs = self.inner.next_ofs - index * self.stride();
+            // SAFE: All items are `stride` words long, so `ofs` is the start of an item
+            Some(unsafe { &mut *self.inner.raw_at_mut(ofs) })
+        } else {
+            None
+        }
+    }
+    /// Returns a pointer to the top item on the stack
+    pub fn top(&self) -> Option<&T> {
+        self.inner.top()
+    }
+    /// Returns a pointer to the top item on the stack (unique/mutable)
+    pub fn top_mut(&mut self) -> Option<&mut T> {
+        self.inner.top_mut()
+    }
+    /// Pop the top item off the stack
+    pub fn pop(&mut self) {
+        self.inner.pop()
+    }
+    /// Remove all items from the stack
+    pub fn clear(&mut self) {
+        self.inner.clear()
+    }
+    /// Iterate over the contents of the stack (top first)
+    pub fn iter(&self) -> super::Iter<'_, T, D> {
+        self.inner.iter()
+    }
+    /// Mutably iterate over the contents of the stack (top first)
+    pub fn iter_mut(&mut self) -> super::IterMut<'_, T, D> {
+        self.inner.iter_mut()
+    }
+}
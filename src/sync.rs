@@ -0,0 +1,109 @@
+//! Thread-safe wrappers around the collection types
+use std::sync::{Mutex, MutexGuard};
+
+use fifo::Fifo;
+
+/// A `Fifo` protected by a mutex, allowing it to be shared between threads
+///
+/// Items can't be borrowed out past the lock, so popping either passes the item to a closure,
+/// or (for `str` and `[T]`) returns an owned copy.
+///
+/// ```
+/// let queue = ::stack_dst::sync::SyncFifo::<str, ::stack_dst::buffers::Ptr8>::new();
+/// queue.push_back_str("Hello").unwrap();
+/// queue.push_back_str("World").unwrap();
+/// assert_eq!(queue.pop_front().as_ref().map(|v| &v[..]), Some("Hello"));
+/// assert_eq!(queue.len(), 1);
+/// ```
+pub struct SyncFifo<T: ?Sized, D: ::DataBuf> {
+    inner: Mutex<Fifo<T, D>>,
+}
+// SAFE: All access to the inner queue is serialised by the mutex
+unsafe impl<T: ?Sized + Send, D: ::DataBuf + Send> Send for SyncFifo<T, D> {}
+unsafe impl<T: ?Sized + Send, D: ::DataBuf + Send> Sync for SyncFifo<T, D> {}
+
+impl<T: ?Sized, D: ::DataBuf> SyncFifo<T, D> {
+    /// Construct a new (empty) queue
+    pub fn new() -> Self
+    where
+        D: Default,
+    {
+        Self::with_buffer(D::default())
+    }
+    /// Construct a new (empty) queue using the provided buffer
+    pub fn with_buffer(data: D) -> Self {
+        SyncFifo {
+            inner: Mutex::new(Fifo::with_buffer(data)),
+        }
+    }
+
+    /// Lock the queue, giving access to the full `Fifo` API
+    ///
+    /// Panics if another thread panicked while holding the lock (like `Mutex::lock().unwrap()`),
+    /// as the queue may have been left partially modified. The other methods lock using this.
+    pub fn lock(&self) -> MutexGuard<'_, Fifo<T, D>> {
+        self.inner
+            .lock()
+            .expect("SyncFifo::lock - Poisoned by a panic while locked")
+    }
+
+    /// Returns the number of items in the queue
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+    /// Checks if the queue is currently empty
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Push a value to the end of the queue (without using `Unsize`)
+    pub fn push_back_stable<U, F: FnOnce(&U) -> &T>(&self, v: U, f: F) -> Result<(), U>
+    where
+        (U, D::Inner): crate::AlignmentValid,
+    {
+        self.lock().push_back_stable(v, f)
+    }
+
+    /// Remove the front item, passing it to `f` before it is dropped
+    ///
+    /// ```
+    /// # use std::fmt::Display;
+    /// let queue = ::stack_dst::sync::SyncFifo::<dyn Display, ::stack_dst::buffers::Ptr8>::new();
+    /// queue.push_back_stable(1234, |v| v).unwrap();
+    /// assert_eq!(queue.pop_front_with(|v| v.to_string()), Some("1234".to_owned()));
+    /// assert_eq!(queue.pop_front_with(|v| v.to_string()), None);
+    /// ```
+    pub fn pop_front_with<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> Option<R> {
+        self.lock().pop_front().map(|mut v| f(&mut v))
+    }
+}
+impl<T: ?Sized, D: ::DataBuf + Default> Default for SyncFifo<T, D> {
+    fn default() -> Self {
+        SyncFifo::new()
+    }
+}
+
+impl<D: ::DataBuf> SyncFifo<str, D> {
+    /// Push the contents of a string slice to the end of the queue
+    pub fn push_back_str(&self, v: &str) -> Result<(), ()> {
+        self.lock().push_back_str(v)
+    }
+    /// Remove the front string, returning an owned copy
+    pub fn pop_front(&self) -> Option<::std::string::String> {
+        self.pop_front_with(|v| ::std::string::String::from(&*v))
+    }
+}
+
+impl<D: ::DataBuf, T: Clone> SyncFifo<[T], D>
+where
+    (T, D::Inner): crate::AlignmentValid,
+{
+    /// Push a set of items (cloning out of the input slice)
+    pub fn push_cloned(&self, v: &[T]) -> Result<(), ()> {
+        self.lock().push_cloned(v)
+    }
+    /// Remove the front slice, returning a clone of its items
+    pub fn pop_front(&self) -> Option<::std::vec::Vec<T>> {
+        self.pop_front_with(|v| v.to_vec())
+    }
+}
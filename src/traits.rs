@@ -0,0 +1,150 @@
+//! Traits shared by the collection and value types
+
+/// Common interface for `Stack` and `Fifo`, allowing algorithms to be written over both
+///
+/// "Next" is the item that would be popped - the top of a `Stack`, or the front of a `Fifo`.
+///
+/// ```
+/// use stack_dst::traits::Deque;
+/// fn drain_to_string<Q: Deque<Item = str>>(q: &mut Q) -> String {
+///     let mut rv = String::new();
+///     while let Some(v) = q.peek() {
+///         rv += v;
+///         q.pop();
+///     }
+///     rv
+/// }
+/// let mut queue = ::stack_dst::Fifo::<str, ::stack_dst::buffers::Ptr8>::new();
+/// queue.push_back_str("Hello");
+/// queue.push_back_str("World");
+/// assert_eq!(drain_to_string(&mut queue), "HelloWorld");
+/// ```
+pub trait Deque {
+    /// Type of the contained items
+    type Item: ?Sized;
+
+    /// Returns the number of items in the collection
+    fn len(&self) -> usize;
+    /// Tests if the collection is empty
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Get a reference to the next item
+    fn peek(&self) -> Option<&Self::Item>;
+    /// Get a unique/mutable reference to the next item
+    fn peek_mut(&mut self) -> Option<&mut Self::Item>;
+    /// Remove (and drop) the next item
+    fn pop(&mut self);
+}
+
+impl<T: ?Sized, D: ::DataBuf> Deque for ::Stack<T, D> {
+    type Item = T;
+    fn len(&self) -> usize {
+        self.len()
+    }
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+    fn peek(&self) -> Option<&T> {
+        self.top()
+    }
+    fn peek_mut(&mut self) -> Option<&mut T> {
+        self.top_mut()
+    }
+    fn pop(&mut self) {
+        self.pop()
+    }
+}
+
+impl<T: ?Sized, D: ::DataBuf> Deque for ::Fifo<T, D> {
+    type Item = T;
+    fn len(&self) -> usize {
+        self.len()
+    }
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+    fn peek(&self) -> Option<&T> {
+        self.front()
+    }
+    fn peek_mut(&mut self) -> Option<&mut T> {
+        self.front_mut()
+    }
+    fn pop(&mut self) {
+        self.pop_front();
+    }
+}
+
+/// Read access shared by owned `Value`s and borrowed `ValueRef`s
+///
+/// ```
+/// use stack_dst::traits::ValueView;
+/// use stack_dst::{Value, ValueRef};
+/// fn checksum<V: ValueView<Item = [u8]>>(v: &V) -> u32 {
+///     v.view().iter().map(|&b| b as u32).sum()
+/// }
+/// let owned = Value::<[u8], ::stack_dst::buffers::Ptr8>::new_stable([1u8, 2, 3], |v| v).unwrap();
+/// let bytes = [4u8, 5];
+/// assert_eq!(checksum(&owned), 6);
+/// assert_eq!(checksum(&ValueRef::new(&bytes[..])), 9);
+/// ```
+pub trait ValueView {
+    /// Type of the contained value
+    type Item: ?Sized;
+
+    /// Get a reference to the value
+    fn view(&self) -> &Self::Item;
+    /// Size of the value in bytes
+    fn size_of_val(&self) -> usize {
+        ::core::mem::size_of_val(self.view())
+    }
+}
+
+impl<T: ?Sized, D: ::DataBuf> ValueView for ::Value<T, D> {
+    type Item = T;
+    fn view(&self) -> &T {
+        self
+    }
+}
+
+impl<'a, T: ?Sized> ValueView for ::ValueRef<'a, T> {
+    type Item = T;
+    fn view(&self) -> &T {
+        self
+    }
+}
+
+/// Conversion into a `Value` using `Unsize` (requires the `unsize` feature)
+///
+/// A blanket `From<U>` impl for `Value` would conflict with `impl<T> From<T> for T`, so this
+/// provides the equivalent of `.into()`. Panics if the value doesn't fit in the buffer (use
+/// `Value::new` to handle that case).
+///
+/// ```
+/// use stack_dst::traits::IntoValue;
+/// use stack_dst::Value;
+/// use std::fmt::Debug;
+/// let v: Value<dyn Debug, ::stack_dst::buffers::Ptr2> = 5i32.into_value();
+/// assert_eq!(format!("{:?}", v), "5");
+/// ```
+#[cfg(feature = "unsize")]
+pub trait IntoValue: Sized {
+    /// Convert into a `Value`, panicking if it doesn't fit
+    fn into_value<T: ?Sized, D: ::DataBuf + Default>(self) -> ::Value<T, D>
+    where
+        Self: ::core::marker::Unsize<T>,
+        (Self, D::Inner): ::AlignmentValid;
+}
+#[cfg(feature = "unsize")]
+impl<U> IntoValue for U {
+    fn into_value<T: ?Sized, D: ::DataBuf + Default>(self) -> ::Value<T, D>
+    where
+        Self: ::core::marker::Unsize<T>,
+        (Self, D::Inner): ::AlignmentValid,
+    {
+        match ::Value::new(self) {
+            Ok(v) => v,
+            Err(_) => panic!("IntoValue::into_value - Value too large for the buffer"),
+        }
+    }
+}
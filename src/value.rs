@@ -1,6 +1,6 @@
 //! Single DST stored inline
 
-use core::{marker, mem, ops, ptr};
+use core::{any, fmt, future, hash, marker, mem, ops, pin, ptr, task};
 
 /// Stack-allocated dynamically sized type
 ///
@@ -15,6 +15,12 @@ use core::{marker, mem, ops, ptr};
 ///     .expect("Insufficient size");
 /// assert_eq!( format!("{}", val), "123456" );
 /// ```
+///
+/// # Self-referential types
+/// The stored value lives inside the `Value` (for inline buffers), so moving the `Value` moves the
+/// stored bytes. A `Value` must therefore not hold data that points into itself, unless the `Value`
+/// is pinned in place (e.g. with `pin!` or `Box::pin`) and the data accessed with
+/// [`Value::as_pin_mut`].
 pub struct Value<T: ?Sized, D: ::DataBuf> {
     _pd: marker::PhantomData<T>,
     // Data contains the object data first, then padding, then the pointer information
@@ -22,6 +28,17 @@ pub struct Value<T: ?Sized, D: ::DataBuf> {
 }
 
 impl<T: ?Sized, D: ::DataBuf> Value<T, D> {
+    /// Size of this type in bytes (i.e. `mem::size_of::<Self>()`), for checking stack usage
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// type V = Value<dyn std::any::Any, ::stack_dst::buffers::Ptr2>;
+    /// assert_eq!(V::FOOTPRINT, 2 * std::mem::size_of::<usize>());
+    /// // Usable in constant expressions
+    /// let _arr: [u8; V::FOOTPRINT * 4];
+    /// ```
+    pub const FOOTPRINT: usize = mem::size_of::<Self>();
+
     /// Construct a stack-based DST
     ///
     /// Returns Ok(dst) if the allocation was successful, or Err(val) if it failed
@@ -45,25 +62,36 @@ impl<T: ?Sized, D: ::DataBuf> Value<T, D> {
 
     /// Construct a stack-based DST using a pre-constructed buffer
     ///
-    /// Returns `Ok(dst)` if the allocation was successful, or `Err(val)` if it failed
+    /// Returns `Ok(dst)` if the allocation was successful, or `Err((buffer, val))` if it failed
     ///
     /// ```
     /// # extern crate core;
     /// # use stack_dst::Value;
     /// # use core::fmt::Display;
-    /// # use core::mem::MaybeUninit;
-    /// let val = Value::<dyn Display, _>::in_buffer([MaybeUninit::new(0u64); 2], 1234)
+    /// # use stack_dst::buffers::Array;
+    /// let val = Value::<dyn Display, _>::in_buffer(Array::<u64, 2>::new(), 1234)
+    ///     .ok()
     ///     .expect("Insufficient size");
     /// assert_eq!( format!("{}", val), "1234" );
     /// ```
     #[cfg(feature = "unsize")]
-    pub fn in_buffer<U: marker::Unsize<T>>(buffer: D, val: U) -> Result<Value<T, D>, U>
+    pub fn in_buffer<U: marker::Unsize<T>>(buffer: D, val: U) -> Result<Value<T, D>, (D, U)>
     where
         (U, D::Inner): crate::AlignmentValid,
     {
         Self::in_buffer_stable(buffer, val, |p| p)
     }
 
+    /// Old form of `in_buffer`, which drops the buffer on failure
+    #[cfg(feature = "unsize")]
+    #[deprecated(note = "Use `in_buffer` (which returns the buffer on failure) instead")]
+    pub fn in_buffer_lossy<U: marker::Unsize<T>>(buffer: D, val: U) -> Result<Value<T, D>, U>
+    where
+        (U, D::Inner): crate::AlignmentValid,
+    {
+        Self::in_buffer(buffer, val).map_err(|(_, val)| val)
+    }
+
     /// Construct a stack-based DST (without needing `Unsize`). The closure `get_ref`
     /// must just convert `&U` to `&U` (if the pointers don't match, an assertion triggers)
     ///
@@ -82,20 +110,146 @@ impl<T: ?Sized, D: ::DataBuf> Value<T, D> {
         (U, D::Inner): crate::AlignmentValid,
         D: Default,
     {
-        Self::in_buffer_stable(D::default(), val, get_ref)
+        Self::in_buffer_stable(D::default(), val, get_ref).map_err(|(_, val)| val)
+    }
+
+    /// Construct a stack-based DST using a closure that coerces a mutable reference
+    ///
+    /// The same as `new_stable`, for when the only available coercion is through `&mut`.
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// # use std::fmt::Debug;
+    /// fn as_debug(v: &mut Vec<u8>) -> &mut (dyn Debug + 'static) { v }
+    /// let v = Value::<dyn Debug, ::stack_dst::buffers::Ptr8>::new_stable_mut(vec![1u8], as_debug).unwrap();
+    /// assert_eq!(format!("{:?}", v), "[1]");
+    /// ```
+    pub fn new_stable_mut<U, F: FnOnce(&mut U) -> &mut T>(
+        mut val: U,
+        get_ref: F,
+    ) -> Result<Value<T, D>, U>
+    where
+        (U, D::Inner): crate::AlignmentValid,
+        D: Default,
+    {
+        let buffer = D::default();
+        crate::check_value_alignment::<U, D>(&buffer);
+
+        let rv = unsafe {
+            let ptr = crate::check_fat_pointer_mut(&mut val, get_ref);
+            match super::decompose_pointer(ptr) {
+                Some((raw_ptr, meta_len, meta)) => Value::new_raw_unchecked(
+                    &meta[..meta_len],
+                    raw_ptr as *mut _,
+                    mem::size_of::<U>(),
+                    buffer,
+                )
+                .ok(),
+                None => None,
+            }
+        };
+        match rv {
+            Some(r) => {
+                // Prevent the destructor from running, now that we've copied it away
+                mem::forget(val);
+                Ok(r)
+            }
+            None => Err(val),
+        }
+    }
+
+    /// Construct a stack-based DST by cloning a borrowed value (see `new_stable` for the
+    /// requirements on `get_ref`)
+    ///
+    /// The size is checked before `val` is cloned, so nothing is cloned if it doesn't fit.
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// # use std::fmt::Debug;
+    /// let orig = vec![1u8, 2];
+    /// let v = Value::<dyn Debug, ::stack_dst::buffers::Ptr8>::new_cloned(&orig, |v| v as _).unwrap();
+    /// assert_eq!(format!("{:?}", v), "[1, 2]");
+    /// assert!(Value::<dyn Debug, ::stack_dst::buffers::Ptr2>::new_cloned(&orig, |v| v as _).is_err());
+    /// ```
+    pub fn new_cloned<U: Clone, F: FnOnce(&U) -> &T>(val: &U, get_ref: F) -> Result<Value<T, D>, ()>
+    where
+        (U, D::Inner): crate::AlignmentValid,
+        D: Default,
+    {
+        let mut buffer = D::default();
+        crate::check_value_alignment::<U, D>(&buffer);
+
+        // The metadata only depends on the type, so can be taken from the original
+        let ptr: *const _ = crate::check_fat_pointer(val, get_ref);
+        let (_, meta_len, meta) = super::decompose_pointer(ptr).ok_or(())?;
+        let info = &meta[..meta_len];
+        let req_words =
+            D::round_to_words(mem::size_of_val(info)) + D::round_to_words(mem::size_of::<U>());
+        crate::extend_buf(&mut buffer, false, req_words)?;
+
+        let mut v = val.clone();
+        // SAFE: `v` is forgotten if it was moved into the value, and dropped here otherwise
+        match unsafe {
+            Value::new_raw_unchecked(
+                info,
+                &mut v as *mut U as *mut (),
+                mem::size_of::<U>(),
+                buffer,
+            )
+        } {
+            Ok(r) => {
+                mem::forget(v);
+                Ok(r)
+            }
+            Err(_) => Err(()),
+        }
+    }
+
+    /// Construct a stack-based DST (without needing `Unsize`), with an error type that works with `?`
+    ///
+    /// Unlike `new_stable`, this returns `PushError::Misaligned` instead of panicking if `U` is
+    /// more strictly aligned than the buffer.
+    ///
+    /// ```
+    /// # use stack_dst::{CapacityError, Value};
+    /// # use std::fmt::Display;
+    /// fn make(v: u32) -> Result<Value<dyn Display, ::stack_dst::buffers::Ptr2>, CapacityError<u32>> {
+    ///     Ok(Value::try_new_stable(v, |v| v as _)?)
+    /// }
+    /// assert_eq!(format!("{}", make(1234).unwrap()), "1234");
+    /// ```
+    pub fn try_new_stable<U, F: FnOnce(&U) -> &T>(
+        val: U,
+        get_ref: F,
+    ) -> Result<Value<T, D>, crate::CapacityError<U>>
+    where
+        (U, D::Inner): crate::AlignmentValid,
+        D: Default,
+    {
+        let buffer = D::default();
+        if !crate::value_alignment_fits::<U, D>(&buffer) {
+            return Err(crate::CapacityError::new(val, crate::PushError::Misaligned));
+        }
+        Self::in_buffer_stable(buffer, val, get_ref).map_err(|(_, val)| {
+            let words = D::round_to_words(mem::size_of::<&T>() - mem::size_of::<usize>())
+                + D::round_to_words(mem::size_of::<U>());
+            crate::CapacityError::new(val, crate::PushError::classify::<D>(words, words))
+        })
     }
 
     /// Construct a stack-based DST (without needing `Unsize`) using a provided buffer.
     /// See `new_stable` for requirements on the `get_ref` closure.
     ///
-    /// Returns `Ok(dst)` if the allocation was successful, or `Err(val)` if it failed
+    /// Returns `Ok(dst)` if the allocation was successful, or `Err((buffer, val))` if it failed
+    /// (allowing the buffer to be reused)
     ///
     /// ```
     /// # extern crate core;
     /// # use stack_dst::Value;
     /// # use core::fmt::Display;
-    /// # use core::mem::MaybeUninit;
-    /// let val = Value::<dyn Display, _>::in_buffer_stable([MaybeUninit::new(0u64); 2], 1234, |v| v)
+    /// # use stack_dst::buffers::Array;
+    /// let val = Value::<dyn Display, _>::in_buffer_stable(Array::<u64, 2>::new(), 1234, |v| v)
+    ///     .ok()
     ///     .expect("Insufficient size");
     /// assert_eq!( format!("{}", val), "1234" );
     /// ```
@@ -103,29 +257,138 @@ impl<T: ?Sized, D: ::DataBuf> Value<T, D> {
         buffer: D,
         val: U,
         get_ref: F,
-    ) -> Result<Value<T, D>, U>
+    ) -> Result<Value<T, D>, (D, U)>
     where
         (U, D::Inner): crate::AlignmentValid,
     {
-        <(U, D::Inner) as crate::AlignmentValid>::check();
+        crate::check_value_alignment::<U, D>(&buffer);
 
         let rv = unsafe {
             let ptr: *const _ = crate::check_fat_pointer(&val, get_ref);
-            let (raw_ptr, meta_len, meta) = super::decompose_pointer(ptr);
-
-            Value::new_raw(
-                &meta[..meta_len],
-                raw_ptr as *mut _,
-                mem::size_of::<U>(),
-                buffer,
-            )
+            match super::decompose_pointer(ptr) {
+                Some((raw_ptr, meta_len, meta)) => Value::new_raw_unchecked(
+                    &meta[..meta_len],
+                    raw_ptr as *mut _,
+                    mem::size_of::<U>(),
+                    buffer,
+                ),
+                None => Err(buffer),
+            }
         };
         match rv {
-            Some(r) => {
+            Ok(r) => {
                 // Prevent the destructor from running, now that we've copied it away
                 mem::forget(val);
                 Ok(r)
             }
+            Err(buffer) => Err((buffer, val)),
+        }
+    }
+
+    /// Old form of `in_buffer_stable`, which drops the buffer on failure
+    #[deprecated(note = "Use `in_buffer_stable` (which returns the buffer on failure) instead")]
+    pub fn in_buffer_stable_lossy<U, F: FnOnce(&U) -> &T>(
+        buffer: D,
+        val: U,
+        get_ref: F,
+    ) -> Result<Value<T, D>, U>
+    where
+        (U, D::Inner): crate::AlignmentValid,
+    {
+        Self::in_buffer_stable(buffer, val, get_ref).map_err(|(_, val)| val)
+    }
+
+    /// Construct by copying the value behind an already-coerced reference
+    ///
+    /// This separates the coercion from the construction, so the coercion can be chosen at
+    /// runtime. Returns `Err` if the value doesn't fit in (or is more aligned than) the buffer.
+    ///
+    /// UNSAFE: Ownership of `*val` is moved into the returned value, so the original must not be
+    /// used or dropped afterwards if this returns `Ok` (e.g. by storing it in a `ManuallyDrop`).
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// # use std::fmt::Display;
+    /// # use std::mem::ManuallyDrop;
+    /// let v = ManuallyDrop::new(1234);
+    /// let r: &dyn Display = &*v;
+    /// let val = unsafe { Value::<dyn Display, ::stack_dst::buffers::Ptr2>::new_from_ref(r) }.unwrap();
+    /// assert_eq!(val.to_string(), "1234");
+    /// ```
+    pub unsafe fn new_from_ref(val: &T) -> Result<Value<T, D>, ()>
+    where
+        D: Default,
+    {
+        let buffer = D::default();
+        let align = mem::align_of_val(val);
+        if align > buffer.data_alignment() && align > mem::align_of::<D::Inner>() {
+            return Err(());
+        }
+        let (raw_ptr, meta_len, meta) = super::decompose_pointer(val as *const T).ok_or(())?;
+        Value::new_raw_unchecked(
+            &meta[..meta_len],
+            raw_ptr as *mut _,
+            mem::size_of_val(val),
+            buffer,
+        )
+        .map_err(|_| ())
+    }
+
+    /// Construct a stack-based DST that also records the concrete type, allowing it to be
+    /// downcast later (see `TypedValue`)
+    ///
+    /// The `TypeId` is stored in the buffer alongside the metadata, so takes extra space.
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// # use std::fmt::Display;
+    /// let val = Value::<dyn Display, ::stack_dst::buffers::Ptr8>::new_stable_typed(1234u32, |v| v as _)
+    ///     .expect("Insufficient size");
+    /// assert_eq!(format!("{}", &*val), "1234");
+    /// assert_eq!(val.downcast_ref_typed::<u32>(), Some(&1234));
+    /// assert_eq!(val.downcast_ref_typed::<i32>(), None);
+    /// ```
+    pub fn new_stable_typed<U: 'static, F: FnOnce(&U) -> &T>(
+        val: U,
+        get_ref: F,
+    ) -> Result<TypedValue<T, D>, U>
+    where
+        (U, D::Inner): crate::AlignmentValid,
+        D: Default,
+    {
+        let buffer = D::default();
+        crate::check_value_alignment::<U, D>(&buffer);
+
+        let rv = unsafe {
+            let ptr: *const _ = crate::check_fat_pointer(&val, get_ref);
+            super::decompose_pointer(ptr).and_then(|(raw_ptr, meta_len, meta)| {
+                // Info is the type ID and the `Any` upcast function followed by the metadata (so the
+                // metadata is still at the end). Each part is padded to a whole number of buffer
+                // words, so it can be found by counting words back from the end of the buffer.
+                let id_words = padded_info_words::<D>(TYPE_ID_WORDS);
+                let fn_words = padded_info_words::<D>(1);
+                let info_len = id_words + fn_words + padded_info_words::<D>(meta_len);
+                let mut info = [0; 4 * (TYPE_ID_WORDS + 1 + crate::MAX_METADATA_WORDS)];
+                if info_len > info.len() {
+                    return None;
+                }
+                info[..TYPE_ID_WORDS].copy_from_slice(&type_id_words(any::TypeId::of::<U>()));
+                info[id_words] = any_ptr::<U> as AnyPtrFn as usize;
+                info[id_words + fn_words..][..meta_len].copy_from_slice(&meta[..meta_len]);
+                Value::new_raw_unchecked(
+                    &info[..info_len],
+                    raw_ptr as *mut _,
+                    mem::size_of::<U>(),
+                    buffer,
+                )
+                .ok()
+            })
+        };
+        match rv {
+            Some(r) => {
+                mem::forget(val);
+                Ok(TypedValue(r))
+            }
             None => Err(val),
         }
     }
@@ -161,19 +424,58 @@ impl<T: ?Sized, D: ::DataBuf> Value<T, D> {
         info: &[usize],
         data: *mut (),
         size: usize,
-        mut buffer: D,
+        buffer: D,
     ) -> Option<Value<T, D>> {
-        let req_words = D::round_to_words(mem::size_of_val(info)) + D::round_to_words(size);
-        if let Err(_) = buffer.extend(req_words) {
+        if info.len() > crate::MAX_METADATA_WORDS {
             return None;
         }
+        Self::new_raw_unchecked(info, data, size, buffer).ok()
+    }
+
+    /// Inner of `new_raw`, `info` can contain extra words before the metadata
+    ///
+    /// Returns the buffer if it couldn't be extended
+    unsafe fn new_raw_unchecked(
+        info: &[usize],
+        data: *mut (),
+        size: usize,
+        mut buffer: D,
+    ) -> Result<Value<T, D>, D> {
+        let req_words = D::round_to_words(mem::size_of_val(info)) + D::round_to_words(size);
+        if let Err(_) = crate::extend_buf(&mut buffer, false, req_words) {
+            return Err(buffer);
+        }
 
         let mut rv = mem::ManuallyDrop::new(Value::<T, D> {
             _pd: marker::PhantomData,
             data: buffer,
         });
         rv.write_value(data, size, info);
-        Some(mem::ManuallyDrop::into_inner(rv))
+        Ok(mem::ManuallyDrop::into_inner(rv))
+    }
+
+    /// Take over a buffer holding an item (e.g. from a `Stack`), moving the item into place
+    ///
+    /// UNSAFE: `item` must point to a valid `T` within `buffer`, aligned for `D::Inner`. The
+    /// buffer is moved out of `buffer` (by `ptr::read`), so the caller must not use or drop it
+    pub(crate) unsafe fn take_from_buffer(buffer: &mut D, item: *mut T) -> Self {
+        let size = mem::size_of_val(&*item);
+        let (_, meta_len, meta) =
+            crate::decompose_pointer(item).expect("Stored item has too much metadata");
+        let info = &meta[..meta_len];
+        // Data to the start (can overlap the current location), then metadata at the end
+        ptr::copy(
+            item as *const u8,
+            buffer.as_mut().as_mut_ptr() as *mut u8,
+            size,
+        );
+        let buf = buffer.as_mut();
+        let info_ofs = buf.len() - D::round_to_words(mem::size_of_val(info));
+        crate::store_metadata(&mut buf[info_ofs..], info);
+        Value {
+            _pd: marker::PhantomData,
+            data: ptr::read(buffer),
+        }
     }
 
     unsafe fn write_value(&mut self, data: *const (), size: usize, info: &[usize]) {
@@ -209,16 +511,19 @@ impl<T: ?Sized, D: ::DataBuf> Value<T, D> {
     where
         (U, D::Inner): crate::AlignmentValid,
     {
-        <(U, D::Inner) as crate::AlignmentValid>::check();
+        crate::check_value_alignment::<U, D>(&self.data);
 
         let size = mem::size_of::<U>();
         let (raw_ptr, meta_len, meta) =
-            super::decompose_pointer(crate::check_fat_pointer(&val, get_ref));
+            match super::decompose_pointer(crate::check_fat_pointer(&val, get_ref)) {
+                Some(v) => v,
+                None => return Err(val),
+            };
         let info = &meta[..meta_len];
 
         // Check size requirements (allow resizing)
         let req_words = D::round_to_words(mem::size_of_val(info)) + D::round_to_words(size);
-        if let Err(_) = self.data.extend(req_words) {
+        if let Err(_) = crate::extend_buf(&mut self.data, false, req_words) {
             return Err(val);
         }
         // If met, drop the existing item and move in the new item
@@ -248,259 +553,2143 @@ impl<T: ?Sized, D: ::DataBuf> Value<T, D> {
         self.replace_stable(val, |v| v)
     }
 
-    /// Obtain raw pointer to the contained data
-    unsafe fn as_ptr(&self) -> *mut T {
-        let data = self.data.as_ref();
-        let info_size = mem::size_of::<*mut T>() / mem::size_of::<usize>() - 1;
-        let info_ofs = data.len() - D::round_to_words(info_size * mem::size_of::<usize>());
-        let (data, meta) = data.split_at(info_ofs);
-        super::make_fat_ptr(data.as_ptr() as *mut (), meta)
-    }
-
-    /// Obtain raw pointer to the contained data
-    unsafe fn as_ptr_mut(&mut self) -> *mut T {
-        let data = self.data.as_mut();
-        let info_size = mem::size_of::<*mut T>() / mem::size_of::<usize>() - 1;
-        let info_ofs = data.len() - D::round_to_words(info_size * mem::size_of::<usize>());
-        let (data, meta) = data.split_at_mut(info_ofs);
-        super::make_fat_ptr(data.as_mut_ptr() as *mut (), meta)
-    }
-}
-/// Specialisations for `str` (allowing storage of strings with single-byte alignment)
-impl<D: ::DataBuf> Value<str, D> {
-    /// Create a new empty string with a default buffer
-    pub fn empty_str() -> Result<Self, ()>
-    where
-        D: Default,
+    /// Transform the contained value in place, into a type with the same size (and no stricter
+    /// alignment)
+    ///
+    /// The value is moved out, passed to `f`, and the result is written back into the same bytes
+    /// (with the metadata updated using `get_ref`).
+    ///
+    /// NOTE: If `f` or `get_ref` panics, the process is aborted (the old value has already been
+    /// moved out, so there is nothing valid left to drop).
+    ///
+    /// Panics (before `f` is called) if `U` and `U2` differ in size, or if `U2` is more strictly
+    /// aligned than `U`.
+    ///
+    /// UNSAFE: The contained value must be a `U` (e.g. checked using `Any`)
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// # use std::fmt::Display;
+    /// let mut value = Value::<dyn Display, ::stack_dst::buffers::Ptr2>::new_stable(1234u32, |v| v)
+    ///     .unwrap();
+    /// unsafe {
+    ///     value.transmute_contents(|v: u32| v as f32 / 4.0, |v: &f32| v);
+    /// }
+    /// assert_eq!(format!("{}", value), "308.5");
+    /// ```
+    pub unsafe fn transmute_contents<U, U2>(
+        &mut self,
+        f: impl FnOnce(U) -> U2,
+        get_ref: impl FnOnce(&U2) -> &T,
+    ) where
+        (U, U2): crate::LayoutMatches,
     {
-        Self::empty_str_in_buffer(Default::default())
+        <(U, U2) as crate::LayoutMatches>::check();
+        debug_assert!(mem::size_of_val::<T>(&**self) == mem::size_of::<U>());
+
+        struct AbortOnUnwind;
+        impl Drop for AbortOnUnwind {
+            fn drop(&mut self) {
+                // A panic while already panicking aborts
+                panic!("Value::transmute_contents - Panic while the value was moved out");
+            }
+        }
+
+        let guard = AbortOnUnwind;
+        let old = ptr::read(self.data.as_ref().as_ptr() as *const U);
+        let new = f(old);
+        let (raw_ptr, meta_len, meta) =
+            super::decompose_pointer(crate::check_fat_pointer(&new, get_ref))
+                .expect("BUG: Metadata size changed");
+        self.write_value(raw_ptr, mem::size_of::<U2>(), &meta[..meta_len]);
+        mem::forget(new);
+        mem::forget(guard);
     }
-    /// Create a new empty string with a provided buffer
-    pub fn empty_str_in_buffer(buffer: D) -> Result<Self, ()> {
-        let rv = unsafe {
-            let (raw_ptr, meta_len, meta) = super::decompose_pointer("");
 
-            Value::new_raw(&meta[..meta_len], raw_ptr as *mut (), 0, buffer)
-        };
-        match rv {
-            Some(r) => Ok(r),
-            None => Err(()),
+    /// Move the contained value into a heap allocation
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let v = Value::<str, ::stack_dst::buffers::Ptr8>::new_str("Hello").unwrap();
+    /// let b: Box<str> = v.into_box();
+    /// assert_eq!(&*b, "Hello");
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn into_box(self) -> ::alloc::boxed::Box<T> {
+        let size = mem::size_of_val::<T>(&self);
+        let align = mem::align_of_val::<T>(&self);
+        let this = mem::ManuallyDrop::new(self);
+        // SAFE: The value is moved (bitwise) into the new allocation, and then only the buffer is
+        // dropped
+        unsafe {
+            let layout = ::alloc::alloc::Layout::from_size_align_unchecked(size, align);
+            let dst = if size == 0 {
+                // Zero-sized values just need an aligned non-null pointer
+                align as *mut u8
+            } else {
+                let p = ::alloc::alloc::alloc(layout);
+                if p.is_null() {
+                    ::alloc::alloc::handle_alloc_error(layout);
+                }
+                p
+            };
+            ptr::copy_nonoverlapping(this.as_ptr() as *const u8, dst, size);
+            let rv = {
+                let data = this.data.as_ref();
+                let info_size = mem::size_of::<*mut T>() / mem::size_of::<usize>() - 1;
+                let info_ofs = data.len() - D::round_to_words(info_size * mem::size_of::<usize>());
+                super::make_fat_ptr::<T, _>(dst as *mut (), &data[info_ofs..])
+            };
+            drop(ptr::read(&this.data));
+            ::alloc::boxed::Box::from_raw(rv)
         }
     }
-    /// Construct from a `str` using a default-constructed buffer
+
+    /// Move the contained value onto the heap, and leak it to get a `'static` reference
+    ///
+    /// NOTE: The memory is never freed, so this should only be used for one-time initialisation
+    ///
     /// ```
-    /// # extern crate core;
     /// # use stack_dst::Value;
-    /// # use core::fmt::Display;
-    /// let val = Value::<str, stack_dst::buffers::U8_32>::new_str("Hello, World")
-    ///     .expect("Insufficient size");
-    /// assert_eq!( &val[..], "Hello, World" );
+    /// let v = Value::<[u8], ::stack_dst::buffers::Ptr8>::new_stable([1, 2, 3], |p| p).unwrap();
+    /// let r: &'static mut [u8] = v.leak();
+    /// r[0] = 5;
+    /// assert_eq!(r, &[5, 2, 3]);
     /// ```
-    pub fn new_str(v: &str) -> Result<Self, &str>
+    #[cfg(feature = "alloc")]
+    pub fn leak(self) -> &'static mut T
     where
-        D: Default,
+        T: 'static,
     {
-        Self::new_str_in_buffer(Default::default(), v)
+        ::alloc::boxed::Box::leak(self.into_box())
     }
-    /// Construct from a `str` using a provided buffer
+
+    /// Hash the raw bytes of the value along with the pointer metadata (e.g. slice length or
+    /// vtable), without requiring `T: Hash`
+    ///
+    /// Two values with identical bytes and metadata hash equally (note that a vtable pointer
+    /// identifies the concrete type).
+    ///
+    /// UNSAFE: The contained value must not contain any uninitialised (e.g. padding) bytes
     ///
     /// ```
-    /// # extern crate core;
     /// # use stack_dst::Value;
-    /// # use core::fmt::Display;
-    /// # use core::mem::MaybeUninit;
-    /// let val = Value::new_str_in_buffer([MaybeUninit::new(0u8); 32], "Hello, World")
-    ///     .expect("Insufficient size");
-    /// assert_eq!( &val[..], "Hello, World" );
+    /// # use std::collections::hash_map::DefaultHasher;
+    /// # use std::hash::Hasher;
+    /// let v = Value::<[u16], ::stack_dst::buffers::Ptr8>::new_stable([1, 2, 3], |v| v).unwrap();
+    /// let mut h = DefaultHasher::new();
+    /// unsafe { v.hash_bytes(&mut h) };
+    /// let _ = h.finish();
     /// ```
-    pub fn new_str_in_buffer(buffer: D, val: &str) -> Result<Self, &str> {
-        let rv = unsafe {
-            let (raw_ptr, meta_len, meta) = super::decompose_pointer(val);
-
-            Value::new_raw(
-                &meta[..meta_len],
-                raw_ptr as *mut (),
-                mem::size_of_val(val),
-                buffer,
-            )
-        };
-        match rv {
-            Some(r) => Ok(r),
-            None => Err(val),
+    pub unsafe fn hash_bytes<H: hash::Hasher>(&self, h: &mut H) {
+        let ptr: *const T = &**self;
+        let (_, meta_len, meta) =
+            super::decompose_pointer(ptr).expect("BUG: Metadata size changed");
+        for w in &meta[..meta_len] {
+            h.write_usize(*w);
         }
+        h.write(::core::slice::from_raw_parts(
+            ptr as *const u8,
+            mem::size_of_val(&**self),
+        ));
     }
 
-    /// Add a string to the end of a string
+    /// Call `f` with the raw bytes of the stored value (excluding the pointer metadata), e.g. to
+    /// checksum the payload
+    ///
+    /// UNSAFE: The contained value must not contain any uninitialised (e.g. padding) bytes
     ///
     /// ```
     /// # use stack_dst::Value;
-    /// let mut s = Value::<str, stack_dst::buffers::Ptr8>::new_str("Foo").unwrap();
-    /// s.append_str("Bar").unwrap();
-    /// assert_eq!(&s[..], "FooBar");
+    /// let v = Value::<[u16], ::stack_dst::buffers::Ptr8>::new_stable([1, 2, 3], |v| v).unwrap();
+    /// let sum = unsafe { v.with_payload_bytes(|b| b.iter().map(|&b| b as u32).sum::<u32>()) };
+    /// assert_eq!(sum, 6);
     /// ```
-    pub fn append_str(&mut self, val: &str) -> Result<(), ()> {
-        let info_words = D::round_to_words(mem::size_of::<usize>());
-
-        let ofs = self.len();
-
-        // Check/expand sufficient space
-        let req_words = D::round_to_words(ofs + val.len()) + info_words;
-        if let Err(_) = self.data.extend(req_words) {
-            return Err(());
-        }
-
-        // Get the metadata slot
-        let data = self.data.as_mut();
-        let info_ofs = data.len() - info_words;
-
-        unsafe {
-            ptr::copy_nonoverlapping(
-                val.as_ptr(),
-                (data.as_mut_ptr() as *mut u8).add(ofs),
-                val.len(),
-            );
-            crate::store_metadata(&mut data[info_ofs..], &[ofs + val.len()]);
-        }
-
-        Ok(())
+    pub unsafe fn with_payload_bytes<R>(&self, f: impl FnOnce(&[u8]) -> R) -> R {
+        let ptr: *const T = &**self;
+        f(::core::slice::from_raw_parts(
+            ptr as *const u8,
+            mem::size_of_val(&**self),
+        ))
     }
 
-    /// Resize the string (discarding trailing data)
+    /// Returns `true` if both references are to the same `Value` (rather than comparing contents)
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let a = Value::<str, ::stack_dst::buffers::Ptr8>::new_str("Hello").unwrap();
+    /// let b = Value::<str, ::stack_dst::buffers::Ptr8>::new_str("Hello").unwrap();
+    /// assert!(a.ptr_eq(&a));
+    /// assert!(!a.ptr_eq(&b));
+    /// ```
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        ptr::eq(self, other)
+    }
+
+    /// Call `f` with a mutable reference to the stored value, returning its result
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let mut v = Value::<[u8], ::stack_dst::buffers::Ptr8>::new_stable([3, 1, 2], |v| v).unwrap();
+    /// let first = v.with_mut(|s| { s.sort(); s[0] });
+    /// assert_eq!(first, 1);
+    /// assert_eq!(&v[..], &[1, 2, 3]);
+    /// ```
+    pub fn with_mut<R, F: FnOnce(&mut T) -> R>(&mut self, f: F) -> R {
+        f(&mut **self)
+    }
+
+    /// Get a pointer to the start of the stored data
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let v = Value::<[u8], ::stack_dst::buffers::Ptr8>::new_stable([1, 2, 3], |v| v).unwrap();
+    /// assert_eq!(v.data_ptr(), v.as_ptr() as *const ());
+    /// ```
+    pub fn data_ptr(&self) -> *const () {
+        self.data.as_ref().as_ptr() as *const ()
+    }
+
+    /// Get the raw bytes of the value and its pointer metadata, e.g. to send to another process
+    ///
+    /// Returns the data bytes, the metadata words (zero-padded), and the number of metadata
+    /// words. See `from_stored_bytes` to reconstruct the value.
+    ///
+    /// The metadata is only meaningful within the same build of the same program (e.g. a vtable
+    /// is an address in the program's memory), so this is only suitable for plain data (such as
+    /// `[u8]` or `str`) passed between identical builds on the same target.
+    ///
+    /// UNSAFE: The contained value must not contain any uninitialised (e.g. padding) bytes
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let v = Value::<[u8], ::stack_dst::buffers::Ptr8>::new_stable([1, 2, 3], |v| v).unwrap();
+    /// let (bytes, meta, meta_len) = unsafe { v.as_stored_bytes() };
+    /// assert_eq!(bytes, &[1, 2, 3]);
+    /// assert_eq!(&meta[..meta_len], &[3]);
+    /// ```
+    pub unsafe fn as_stored_bytes(&self) -> (&[u8], [usize; crate::MAX_METADATA_WORDS], usize) {
+        let ptr = self.as_ptr() as *const T;
+        let (_, meta_len, meta) =
+            super::decompose_pointer(ptr).expect("BUG: Metadata size changed");
+        let bytes = ::core::slice::from_raw_parts(ptr as *const u8, mem::size_of_val(&**self));
+        (bytes, meta, meta_len)
+    }
+
+    /// Reconstruct a value from the output of `as_stored_bytes`
+    ///
+    /// Returns `Err` if the value doesn't fit in the buffer, or if `meta` is the wrong length for
+    /// `T`.
+    ///
+    /// UNSAFE: `bytes` and `meta` must have come from `as_stored_bytes` on a `Value<T, _>` in the
+    /// same build of the program, and the buffer must be at least as aligned as the value's type.
+    /// If `T` has a destructor or owns resources, the original value must not also be dropped.
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let v = Value::<str, ::stack_dst::buffers::Ptr8>::new_str("Hello").unwrap();
+    /// let (bytes, meta, meta_len) = unsafe { v.as_stored_bytes() };
+    /// let v2 = unsafe {
+    ///     Value::<str, ::stack_dst::buffers::Ptr8>::from_stored_bytes(bytes, &meta[..meta_len])
+    /// }
+    /// .unwrap();
+    /// assert_eq!(&v2[..], "Hello");
+    /// ```
+    pub unsafe fn from_stored_bytes(bytes: &[u8], meta: &[usize]) -> Result<Self, ()>
+    where
+        D: Default,
+    {
+        let info_size = mem::size_of::<*mut T>() / mem::size_of::<usize>() - 1;
+        if meta.len() != info_size {
+            return Err(());
+        }
+        Self::new_raw(meta, bytes.as_ptr() as *mut (), bytes.len(), D::default()).ok_or(())
+    }
+
+    /// Obtain raw pointer to the contained data
+    unsafe fn as_ptr(&self) -> *mut T {
+        let data = self.data.as_ref();
+        let info_size = mem::size_of::<*mut T>() / mem::size_of::<usize>() - 1;
+        let info_ofs = data.len() - D::round_to_words(info_size * mem::size_of::<usize>());
+        let (data, meta) = data.split_at(info_ofs);
+        super::make_fat_ptr(data.as_ptr() as *mut (), meta)
+    }
+
+    /// Obtain raw pointer to the contained data
+    unsafe fn as_ptr_mut(&mut self) -> *mut T {
+        let data = self.data.as_mut();
+        let info_size = mem::size_of::<*mut T>() / mem::size_of::<usize>() - 1;
+        let info_ofs = data.len() - D::round_to_words(info_size * mem::size_of::<usize>());
+        let (data, meta) = data.split_at_mut(info_ofs);
+        super::make_fat_ptr(data.as_mut_ptr() as *mut (), meta)
+    }
+}
+/// Pinned access
+///
+/// A `Value` is only `Unpin` if `T` is, so once a `Value` holding a `!Unpin` type is pinned, no
+/// safe method can move the stored data (e.g. `replace` needs `&mut Value`).
+impl<T: ?Sized, D: ::DataBuf> Value<T, D> {
+    /// Get a pinned reference to the stored value (which is structurally pinned)
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// # use std::marker::PhantomPinned;
+    /// # use std::pin::Pin;
+    /// trait Anchor {
+    ///     fn anchor(self: Pin<&mut Self>);
+    ///     fn is_anchored(&self) -> bool;
+    /// }
+    /// struct SelfRef(*const SelfRef, PhantomPinned);
+    /// impl Anchor for SelfRef {
+    ///     fn anchor(self: Pin<&mut Self>) {
+    ///         // SAFE: Doesn't move the value
+    ///         let s = unsafe { self.get_unchecked_mut() };
+    ///         s.0 = s;
+    ///     }
+    ///     fn is_anchored(&self) -> bool {
+    ///         self.0 == self
+    ///     }
+    /// }
+    /// let v = Value::<dyn Anchor, ::stack_dst::buffers::Ptr8>::new_stable(
+    ///     SelfRef(std::ptr::null(), PhantomPinned),
+    ///     |p| p as _,
+    /// )
+    /// .ok()
+    /// .unwrap();
+    /// let mut v = Box::pin(v);
+    /// v.as_mut().as_pin_mut().anchor();
+    /// let v2 = v; // Only moves the box, not the value
+    /// assert!(v2.is_anchored());
+    /// ```
+    ///
+    /// Once pinned, a `!Unpin` value can't be replaced (or otherwise moved)
+    /// ```compile_fail
+    /// # use stack_dst::Value;
+    /// # use std::any::Any;
+    /// let v = Value::<dyn Any, ::stack_dst::buffers::Ptr8>::new_stable(1u32, |p| p as _).unwrap();
+    /// let mut v = Box::pin(v);
+    /// // Error: `Value<dyn Any>` is `!Unpin`, so `Pin` doesn't give out `&mut Value`
+    /// v.as_mut().replace_stable(2u32, |p| p as _).unwrap();
+    /// ```
+    pub fn as_pin_mut(self: pin::Pin<&mut Self>) -> pin::Pin<&mut T> {
+        // SAFE: The data is pinned whenever `self` is (see above)
+        unsafe { pin::Pin::new_unchecked(&mut **self.get_unchecked_mut()) }
+    }
+    /// Get a pinned shared reference to the stored value
+    pub fn as_pin_ref(self: pin::Pin<&Self>) -> pin::Pin<&T> {
+        // SAFE: The data is pinned whenever `self` is (see above)
+        unsafe { pin::Pin::new_unchecked(&**self.get_ref()) }
+    }
+}
+
+/// Specialisations for sized types (no metadata is stored)
+impl<T, D: ::DataBuf> Value<T, D> {
+    /// Construct from a sized value (no coercion, so no metadata needs to be stored)
+    ///
+    /// Returns `Ok(dst)` if the allocation was successful, or `Err(val)` if it failed
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let val = Value::<u64, ::stack_dst::buffers::Ptr1>::new_sized(1234)
+    ///     .expect("Insufficient size");
+    /// assert_eq!(*val, 1234);
+    /// ```
+    pub fn new_sized(val: T) -> Result<Self, T>
+    where
+        (T, D::Inner): crate::AlignmentValid,
+        D: Default,
+    {
+        let buffer = D::default();
+        crate::check_value_alignment::<T, D>(&buffer);
+        // SAFE: `val` is forgotten if the value is moved into the buffer
+        let rv = unsafe {
+            Value::new_raw(
+                &[],
+                &val as *const T as *mut (),
+                mem::size_of::<T>(),
+                buffer,
+            )
+        };
+        match rv {
+            Some(r) => {
+                // Prevent the destructor from running, now that we've copied it away
+                mem::forget(val);
+                Ok(r)
+            }
+            None => Err(val),
+        }
+    }
+}
+/// Specialisations for `str` (allowing storage of strings with single-byte alignment)
+impl<D: ::DataBuf> Value<str, D> {
+    /// Create a new empty string with a default buffer
+    pub fn empty_str() -> Result<Self, ()>
+    where
+        D: Default,
+    {
+        Self::empty_str_in_buffer(Default::default())
+    }
+    /// Start building a string with chained `push_str` calls (see `ValueBuilder`)
+    pub fn builder() -> Result<ValueBuilder<str, D>, ()>
+    where
+        D: Default,
+    {
+        Self::empty_str().map(ValueBuilder)
+    }
+    /// Create a new empty string with a provided buffer
+    pub fn empty_str_in_buffer(buffer: D) -> Result<Self, ()> {
+        let rv = unsafe {
+            super::decompose_pointer("").and_then(|(raw_ptr, meta_len, meta)| {
+                Value::new_raw(&meta[..meta_len], raw_ptr as *mut (), 0, buffer)
+            })
+        };
+        match rv {
+            Some(r) => Ok(r),
+            None => Err(()),
+        }
+    }
+    /// Construct from a `str` using a default-constructed buffer
+    /// ```
+    /// # extern crate core;
+    /// # use stack_dst::Value;
+    /// # use core::fmt::Display;
+    /// let val = Value::<str, stack_dst::buffers::U8_32>::new_str("Hello, World")
+    ///     .expect("Insufficient size");
+    /// assert_eq!( &val[..], "Hello, World" );
+    /// ```
+    pub fn new_str(v: &str) -> Result<Self, &str>
+    where
+        D: Default,
+    {
+        Self::new_str_in_buffer(Default::default(), v)
+    }
+    /// Construct from a `str` using a provided buffer
+    ///
+    /// ```
+    /// # extern crate core;
+    /// # use stack_dst::Value;
+    /// # use core::fmt::Display;
+    /// # use stack_dst::buffers::Array;
+    /// let val = Value::new_str_in_buffer(Array::<u8, 32>::new(), "Hello, World")
+    ///     .expect("Insufficient size");
+    /// assert_eq!( &val[..], "Hello, World" );
+    /// ```
+    pub fn new_str_in_buffer(buffer: D, val: &str) -> Result<Self, &str> {
+        let rv = unsafe {
+            super::decompose_pointer(val).and_then(|(raw_ptr, meta_len, meta)| {
+                Value::new_raw(
+                    &meta[..meta_len],
+                    raw_ptr as *mut (),
+                    mem::size_of_val(val),
+                    buffer,
+                )
+            })
+        };
+        match rv {
+            Some(r) => Ok(r),
+            None => Err(val),
+        }
+    }
+
+    /// Construct a string containing `s` repeated `n` times (like `str::repeat`)
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let s = Value::<str, stack_dst::buffers::Ptr8>::repeat("ab", 3).unwrap();
+    /// assert_eq!(&s[..], "ababab");
+    /// ```
+    pub fn repeat(s: &str, n: usize) -> Result<Self, ()>
+    where
+        D: Default,
+    {
+        let mut rv = Self::empty_str()?;
+        let info_words = D::round_to_words(mem::size_of::<usize>());
+
+        // Check/expand sufficient space (once, for the entire string)
+        let total = s
+            .len()
+            .checked_mul(n)
+            // No allocation can be larger than `isize::MAX`
+            .filter(|&n| n <= isize::MAX as usize)
+            .ok_or(())?;
+        let req_words = D::round_to_words(total).checked_add(info_words).ok_or(())?;
+        if let Err(_) = crate::extend_buf(&mut rv.data, false, req_words) {
+            return Err(());
+        }
+
+        // Get the metadata slot
+        let data = rv.data.as_mut();
+        let info_ofs = data.len() - info_words;
+
+        unsafe {
+            let base = data.as_mut_ptr() as *mut u8;
+            for i in 0..n {
+                ptr::copy_nonoverlapping(s.as_ptr(), base.add(i * s.len()), s.len());
+            }
+            crate::store_metadata(&mut data[info_ofs..], &[total]);
+        }
+
+        Ok(rv)
+    }
+
+    /// Clone into a new default-constructed buffer, returning `Err` if it doesn't fit
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let s = Value::<str, stack_dst::buffers::Ptr8>::new_str("Hello").unwrap();
+    /// assert_eq!(&s.try_clone().unwrap()[..], "Hello");
+    /// ```
+    pub fn try_clone(&self) -> Result<Self, ()>
+    where
+        D: Default,
+    {
+        Self::new_str(self).map_err(|_| ())
+    }
+
+    /// Construct from a `str`, falling back to a `Box<str>` if it doesn't fit
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// # use stack_dst::value::InlineOrBoxed;
+    /// let s = Value::<str, stack_dst::buffers::Ptr2>::new_str_or_boxed("A much longer string");
+    /// if let InlineOrBoxed::Inline(_) = s {
+    ///     panic!("Shouldn't fit inline");
+    /// }
+    /// assert_eq!(&*s, "A much longer string");
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn new_str_or_boxed(v: &str) -> InlineOrBoxed<Self, ::alloc::boxed::Box<str>>
+    where
+        D: Default,
+    {
+        match Self::new_str(v) {
+            Ok(rv) => InlineOrBoxed::Inline(rv),
+            Err(v) => InlineOrBoxed::Boxed(::alloc::boxed::Box::from(v)),
+        }
+    }
+
+    /// Construct by formatting `args` directly into the buffer (a `no_std` equivalent of `format!`)
+    ///
+    /// Returns `Err` if the formatted string doesn't fit.
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let s = Value::<str, stack_dst::buffers::Ptr8>::from_fmt(format_args!("{}-{}", 1, 2)).unwrap();
+    /// assert_eq!(&s[..], "1-2");
+    /// ```
+    pub fn from_fmt(args: fmt::Arguments) -> Result<Self, ()>
+    where
+        D: Default,
+    {
+        let mut rv = Self::empty_str()?;
+        match fmt::Write::write_fmt(&mut rv, args) {
+            Ok(()) => Ok(rv),
+            Err(_) => Err(()),
+        }
+    }
+
+    /// Length of the string in bytes (the same as `str::len`)
+    pub fn len_bytes(&self) -> usize {
+        self.len()
+    }
+    /// Returns the number of bytes the string can hold without growing the buffer
+    ///
+    /// Can be used to check if `append_str` will fit.
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let mut s = Value::<str, stack_dst::buffers::Ptr2>::new_str("Foo").unwrap();
+    /// // One word is used for the length
+    /// assert_eq!(s.capacity_bytes(), std::mem::size_of::<usize>());
+    /// let tail = "Bar";
+    /// let fits = s.len_bytes() + tail.len() <= s.capacity_bytes();
+    /// assert_eq!(s.append_str(tail).is_ok(), fits);
+    /// ```
+    pub fn capacity_bytes(&self) -> usize {
+        let info_words = D::round_to_words(mem::size_of::<usize>());
+        let data_words = self.data.as_ref().len() - info_words;
+        data_words * mem::size_of::<D::Inner>()
+    }
+
+    /// Add a string to the end of a string
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let mut s = Value::<str, stack_dst::buffers::Ptr8>::new_str("Foo").unwrap();
+    /// s.append_str("Bar").unwrap();
+    /// assert_eq!(&s[..], "FooBar");
+    /// ```
+    pub fn append_str(&mut self, val: &str) -> Result<(), ()> {
+        let info_words = D::round_to_words(mem::size_of::<usize>());
+
+        let ofs = self.len();
+
+        // Check/expand sufficient space
+        let req_words = D::round_to_words(ofs + val.len()) + info_words;
+        if let Err(_) = crate::extend_buf(&mut self.data, false, req_words) {
+            return Err(());
+        }
+
+        // Get the metadata slot
+        let data = self.data.as_mut();
+        let info_ofs = data.len() - info_words;
+
+        unsafe {
+            ptr::copy_nonoverlapping(
+                val.as_ptr(),
+                (data.as_mut_ptr() as *mut u8).add(ofs),
+                val.len(),
+            );
+            crate::store_metadata(&mut data[info_ofs..], &[ofs + val.len()]);
+        }
+
+        Ok(())
+    }
+
+    /// Insert a string at the given byte index
+    ///
+    /// Returns `Err` (leaving the string unchanged) if there isn't enough space, and panics if
+    /// `idx` isn't on a character boundary (like `String::insert_str`)
     ///
     /// ```
     /// # use stack_dst::Value;
     /// let mut s = Value::<str, stack_dst::buffers::Ptr8>::new_str("FooBar").unwrap();
-    /// s.truncate(3);
-    /// assert_eq!(&s[..], "Foo");
+    /// s.insert_str(3, "-").unwrap();
+    /// assert_eq!(&s[..], "Foo-Bar");
+    /// ```
+    pub fn insert_str(&mut self, idx: usize, val: &str) -> Result<(), ()> {
+        assert!(self.is_char_boundary(idx), "`idx` is not a char boundary");
+        let info_words = D::round_to_words(mem::size_of::<usize>());
+
+        let len = self.len();
+
+        // Check/expand sufficient space
+        let req_words = D::round_to_words(len + val.len()) + info_words;
+        if let Err(_) = crate::extend_buf(&mut self.data, false, req_words) {
+            return Err(());
+        }
+
+        // Get the metadata slot
+        let data = self.data.as_mut();
+        let info_ofs = data.len() - info_words;
+
+        unsafe {
+            let base = data.as_mut_ptr() as *mut u8;
+            ptr::copy(base.add(idx), base.add(idx + val.len()), len - idx);
+            ptr::copy_nonoverlapping(val.as_ptr(), base.add(idx), val.len());
+            crate::store_metadata(&mut data[info_ofs..], &[len + val.len()]);
+        }
+
+        Ok(())
+    }
+
+    /// Replace a byte range of the string with `replacement` (like `String::replace_range`)
+    ///
+    /// Returns `Err` (leaving the string unchanged) if the result doesn't fit. Panics if the range
+    /// is out of bounds, or doesn't start and end on character boundaries.
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let mut s = Value::<str, stack_dst::buffers::Ptr8>::new_str("Hello world").unwrap();
+    /// s.replace_range(..5, "Goodbye").unwrap();
+    /// assert_eq!(&s[..], "Goodbye world");
+    /// ```
+    pub fn replace_range<R: ops::RangeBounds<usize>>(
+        &mut self,
+        range: R,
+        replacement: &str,
+    ) -> Result<(), ()> {
+        let len = self.len();
+        let (start, end) = resolve_range(range, len);
+        assert!(
+            self.is_char_boundary(start),
+            "`start` is not a char boundary"
+        );
+        assert!(self.is_char_boundary(end), "`end` is not a char boundary");
+        let info_words = D::round_to_words(mem::size_of::<usize>());
+
+        // Check/expand sufficient space
+        let new_len = len - (end - start) + replacement.len();
+        let req_words = D::round_to_words(new_len) + info_words;
+        if let Err(_) = crate::extend_buf(&mut self.data, false, req_words) {
+            return Err(());
+        }
+
+        let data = self.data.as_mut();
+        let info_ofs = data.len() - info_words;
+        unsafe {
+            let base = data.as_mut_ptr() as *mut u8;
+            ptr::copy(
+                base.add(end),
+                base.add(start + replacement.len()),
+                len - end,
+            );
+            ptr::copy_nonoverlapping(replacement.as_ptr(), base.add(start), replacement.len());
+        }
+        crate::store_metadata(&mut data[info_ofs..], &[new_len]);
+        Ok(())
+    }
+
+    /// Resize the string (discarding trailing data)
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let mut s = Value::<str, stack_dst::buffers::Ptr8>::new_str("FooBar").unwrap();
+    /// s.truncate(3);
+    /// assert_eq!(&s[..], "Foo");
+    /// ```
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            let _ = &self[..][len..]; // Index to force a panic if the index isn't char-aligned
+
+            let info_words = D::round_to_words(mem::size_of::<usize>());
+            let data = self.data.as_mut();
+            let info_ofs = data.len() - info_words;
+            crate::store_metadata(&mut data[info_ofs..], &[len]);
+        }
+    }
+
+    /// Remove leading and trailing whitespace, in place (see `str::trim`)
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let mut s = Value::<str, stack_dst::buffers::Ptr8>::new_str("  Foo Bar\n").unwrap();
+    /// s.trim_in_place();
+    /// assert_eq!(&s[..], "Foo Bar");
+    /// ```
+    pub fn trim_in_place(&mut self) {
+        self.trim_end_in_place();
+        self.trim_start_in_place();
+    }
+    /// Remove leading whitespace, moving the remaining bytes to the front (see `str::trim_start`)
+    pub fn trim_start_in_place(&mut self) {
+        let len = self.len();
+        let start = len - self.trim_start().len();
+        if start > 0 {
+            let info_words = D::round_to_words(mem::size_of::<usize>());
+            let data = self.data.as_mut();
+            let info_ofs = data.len() - info_words;
+            unsafe {
+                let base = data.as_mut_ptr() as *mut u8;
+                ptr::copy(base.add(start), base, len - start);
+            }
+            crate::store_metadata(&mut data[info_ofs..], &[len - start]);
+        }
+    }
+    /// Remove trailing whitespace (see `str::trim_end`)
+    pub fn trim_end_in_place(&mut self) {
+        let len = self.trim_end().len();
+        self.truncate(len);
+    }
+
+    /// Split the string in two at the given byte index, returning the tail in a new value
+    ///
+    /// Panics if `at` is not on a character boundary (or is past the end of the string)
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let mut s = Value::<str, stack_dst::buffers::Ptr8>::new_str("FooBar").unwrap();
+    /// let t = s.split_off(3).unwrap();
+    /// assert_eq!(&s[..], "Foo");
+    /// assert_eq!(&t[..], "Bar");
+    /// ```
+    pub fn split_off(&mut self, at: usize) -> Result<Self, ()>
+    where
+        D: Default,
+    {
+        let rv = Self::new_str(&self[..][at..]).map_err(|_| ())?;
+        self.truncate(at);
+        Ok(rv)
+    }
+
+    /// Move the string out into a new value, leaving an empty string in its place
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let mut s = Value::<str, stack_dst::buffers::Ptr8>::new_str("Foo").unwrap();
+    /// let t = s.take().unwrap();
+    /// assert_eq!(&s[..], "");
+    /// assert_eq!(&t[..], "Foo");
+    /// ```
+    pub fn take(&mut self) -> Result<Self, ()>
+    where
+        D: Default,
+    {
+        let empty = Self::empty_str()?;
+        Ok(mem::replace(self, empty))
+    }
+}
+/// Specialisation for slices (acting like an `ArrayVec`)
+///
+/// The element type must be `Sized` (as for any slice), so a slice of trait objects has to box or
+/// reference them. Using an unsized element type is rejected by the compiler when naming the type.
+///
+/// ```compile_fail,E0277
+/// # use stack_dst::Value;
+/// # use std::fmt::Display;
+/// // error: the size for values of type `dyn Display` cannot be known at compilation time
+/// let _ = Value::<[dyn Display], ::stack_dst::buffers::Ptr8>::empty_slice();
+/// ```
+///
+/// ```
+/// # use stack_dst::Value;
+/// # use std::fmt::Display;
+/// let v = Value::<[&dyn Display], ::stack_dst::buffers::Ptr8>::empty_slice()
+///     .unwrap()
+///     .appended(&1234 as &dyn Display)
+///     .ok()
+///     .unwrap();
+/// assert_eq!(v[0].to_string(), "1234");
+/// ```
+impl<I, D: ::DataBuf> Value<[I], D>
+where
+    (I, D::Inner): crate::AlignmentValid,
+{
+    /// Create a new zero-sized slice (will error only if the metadata doesn't fit)
+    pub fn empty_slice() -> Result<Self, ()>
+    where
+        D: Default,
+    {
+        Self::empty_slice_with_buffer(Default::default())
+    }
+    /// Start building a slice with chained `push` calls (see `ValueBuilder`)
+    pub fn builder() -> Result<ValueBuilder<[I], D>, ()>
+    where
+        D: Default,
+    {
+        Self::empty_slice().map(ValueBuilder)
+    }
+    /// Create a new zero-sized slice in the provided buffer (will error only if the metadata doesn't fit)
+    pub fn empty_slice_with_buffer(mut buffer: D) -> Result<Self, ()> {
+        crate::check_value_alignment::<I, D>(&buffer);
+
+        let info_words = D::round_to_words(mem::size_of::<usize>());
+        let req_words = info_words;
+        if let Err(_) = crate::extend_buf(&mut buffer, false, req_words) {
+            return Err(());
+        }
+        assert!(req_words <= buffer.as_ref().len());
+
+        let mut rv = Value {
+            _pd: marker::PhantomData,
+            data: buffer,
+        };
+
+        let data = rv.data.as_mut();
+        let info_ofs = data.len() - info_words;
+        let (_data_dst, info_dst) = data.split_at_mut(info_ofs);
+
+        crate::store_metadata(info_dst, &[0]);
+        Ok(rv)
+    }
+
+    /// Construct a slice of `len` items, each populated by calling `f` with its index
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let v = Value::<[usize], stack_dst::buffers::Ptr8>::from_fn(5, |i| i * i).unwrap();
+    /// assert_eq!(&v[..], &[0, 1, 4, 9, 16]);
+    /// ```
+    pub fn from_fn<F: FnMut(usize) -> I>(len: usize, f: F) -> Result<Self, ()>
+    where
+        D: Default,
+    {
+        let mut rv = Self::empty_slice()?;
+        let info_words = D::round_to_words(mem::size_of::<usize>());
+        let bytes = len
+            .checked_mul(mem::size_of::<I>())
+            // No allocation can be larger than `isize::MAX`
+            .filter(|&n| n <= isize::MAX as usize)
+            .ok_or(())?;
+        let req_words = D::round_to_words(bytes).checked_add(info_words).ok_or(())?;
+        if let Err(_) = crate::extend_buf(&mut rv.data, false, req_words) {
+            return Err(());
+        }
+        // SAFE: No items are populated, and the metadata slot may have moved during `extend`
+        unsafe {
+            rv.set_len(0);
+        }
+
+        let data = rv.data.as_mut();
+        let info_ofs = data.len() - info_words;
+        let (data_dst, info_dst) = data.split_at_mut(info_ofs);
+        assert!(bytes <= data_dst.len() * mem::size_of::<D::Inner>());
+        // Nothing needs resetting on panic, as the length is only set once all items are written
+        let mut reset_slot = 0;
+        // SAFE: Space has been checked
+        unsafe {
+            crate::list_push_gen(info_dst, data_dst, len, f, &mut reset_slot, 0);
+        }
+        Ok(rv)
+    }
+
+    /// Clone into a new default-constructed buffer, returning `Err` if it doesn't fit
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let v = Value::<[String], stack_dst::buffers::Ptr8>::from_fn(2, |i| i.to_string()).unwrap();
+    /// let v2 = v.try_clone().unwrap();
+    /// assert_eq!(&v2[..], &["0", "1"]);
+    /// ```
+    pub fn try_clone(&self) -> Result<Self, ()>
+    where
+        I: Clone,
+        D: Default,
+    {
+        Self::from_fn(self.len(), |i| self[i].clone())
+    }
+
+    /// Construct a slice by concatenating several slices (e.g. assembling a packet from fragments)
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let v = Value::<[u8], stack_dst::buffers::Ptr8>::concat(&[&[1, 2], &[], &[3]]).unwrap();
+    /// assert_eq!(&v[..], &[1, 2, 3]);
+    /// ```
+    pub fn concat(items: &[&[I]]) -> Result<Self, ()>
+    where
+        I: Copy,
+        D: Default,
+    {
+        let len = items
+            .iter()
+            .try_fold(0usize, |n, v| n.checked_add(v.len()))
+            .ok_or(())?;
+        let bytes = len.checked_mul(mem::size_of::<I>()).ok_or(())?;
+
+        let mut rv = Self::empty_slice()?;
+        let info_words = D::round_to_words(mem::size_of::<usize>());
+        let req_words = D::round_to_words(bytes) + info_words;
+        if let Err(_) = crate::extend_buf(&mut rv.data, false, req_words) {
+            return Err(());
+        }
+        // SAFE: Space has been checked, and the items are `Copy`
+        unsafe {
+            let base = rv.data.as_mut().as_mut_ptr() as *mut I;
+            let mut ofs = 0;
+            for v in items {
+                ptr::copy_nonoverlapping(v.as_ptr(), base.add(ofs), v.len());
+                ofs += v.len();
+            }
+            rv.set_len(len);
+        }
+        Ok(rv)
+    }
+
+    /// Construct from an array, coercing it to a slice
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let val = Value::<[u8], stack_dst::buffers::Ptr2>::from_array([1, 2, 3, 4])
+    ///     .expect("Insufficient size");
+    /// assert_eq!(&val[..], &[1, 2, 3, 4]);
+    /// ```
+    #[cfg(feature = "const_generics")]
+    pub fn from_array<const N: usize>(arr: [I; N]) -> Result<Self, [I; N]>
+    where
+        ([I; N], D::Inner): crate::AlignmentValid,
+        D: Default,
+    {
+        Self::new_stable(arr, |v| v)
+    }
+
+    /// Returns the number of items that can be stored without growing the buffer
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let v = Value::<[u8], stack_dst::buffers::Ptr2>::empty_slice().unwrap();
+    /// // One word is used for the length
+    /// assert_eq!(v.capacity(), std::mem::size_of::<usize>());
+    /// ```
+    pub fn capacity(&self) -> usize {
+        if mem::size_of::<I>() == 0 {
+            return usize::MAX;
+        }
+        let info_words = D::round_to_words(mem::size_of::<usize>());
+        let data_words = self.data.as_ref().len() - info_words;
+        data_words * mem::size_of::<D::Inner>() / mem::size_of::<I>()
+    }
+
+    /// Ensure that there is space for at least `additional` more items (similar to `Vec::reserve`)
+    ///
+    /// Returns `Err` if the buffer can't grow to fit them.
+    ///
+    /// ```
+    /// # #[cfg(feature = "alloc")] {
+    /// # use stack_dst::Value;
+    /// let mut v = Value::<[u32], stack_dst::buffers::PtrVec>::empty_slice().unwrap();
+    /// v.reserve(100).unwrap();
+    /// assert!(v.capacity() >= 100);
+    /// # }
+    /// ```
+    pub fn reserve(&mut self, additional: usize) -> Result<(), ()> {
+        let info_words = D::round_to_words(mem::size_of::<usize>());
+
+        let len = self.len();
+        let bytes = len
+            .checked_add(additional)
+            .and_then(|n| n.checked_mul(mem::size_of::<I>()))
+            // No allocation can be larger than `isize::MAX`
+            .filter(|&n| n <= isize::MAX as usize)
+            .ok_or(())?;
+        let req_words = D::round_to_words(bytes) + info_words;
+        if req_words <= self.data.as_ref().len() {
+            return Ok(());
+        }
+        crate::extend_buf(&mut self.data, false, req_words)?;
+        // SAFE: Length is unchanged, but the metadata slot moves to the new end of the buffer
+        unsafe {
+            self.set_len(len);
+        }
+        Ok(())
+    }
+
+    /// Returns the unused space after the current items (similar to `Vec::spare_capacity_mut`)
+    ///
+    /// Once items have been written, call `set_len` to include them in the slice.
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// # use std::mem::MaybeUninit;
+    /// let mut v = Value::<[u32], stack_dst::buffers::Ptr8>::new_stable([1], |p| p).unwrap();
+    /// let spare = v.spare_capacity_mut();
+    /// spare[0] = MaybeUninit::new(2);
+    /// spare[1] = MaybeUninit::new(3);
+    /// // SAFE: The first three items are now initialised
+    /// unsafe { v.set_len(3) };
+    /// assert_eq!(&v[..], &[1, 2, 3]);
+    /// ```
+    pub fn spare_capacity_mut(&mut self) -> &mut [mem::MaybeUninit<I>] {
+        let len = self.len();
+        let cap = self.capacity();
+        // SAFE: The data is at the start of the buffer (with alignment checked on construction),
+        // and `capacity` only counts items that fit before the metadata.
+        unsafe {
+            let base = self.data.as_mut().as_mut_ptr() as *mut mem::MaybeUninit<I>;
+            core::slice::from_raw_parts_mut(base.add(len), cap - len)
+        }
+    }
+
+    /// Append an item to the end of the slice (similar to `Vec::push`)
+    pub fn append(&mut self, v: I) -> Result<(), I> {
+        let info_words = D::round_to_words(mem::size_of::<usize>());
+
+        let ofs = self.len();
+
+        // Check/expand sufficient space
+        let req_words = D::round_to_words((ofs + 1) * mem::size_of::<I>()) + info_words;
+        if let Err(_) = crate::extend_buf(&mut self.data, false, req_words) {
+            return Err(v);
+        }
+        let data = self.data.as_mut();
+        assert!(req_words <= data.len());
+        // Write the new value
+        // SAFE: Alignment is checked, pointer is in-bounds
+        unsafe {
+            let data_ptr = (data.as_ptr() as *mut I).add(ofs);
+            ptr::write(data_ptr, v);
+        }
+        // Only update item count after the write
+        let info_ofs = data.len() - info_words;
+        crate::store_metadata(&mut data[info_ofs..], &[ofs + 1]);
+
+        Ok(())
+    }
+
+    /// Insert an item into a sorted slice, keeping it sorted (returns the index it was inserted at)
+    ///
+    /// Returns `Err` (with the item) if there isn't enough space.
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let mut v = Value::<[i32], stack_dst::buffers::Ptr8>::new_stable([1, 5], |v| v).unwrap();
+    /// assert_eq!(v.insert_sorted(3), Ok(1));
+    /// assert_eq!(&v[..], &[1, 3, 5]);
+    /// ```
+    pub fn insert_sorted(&mut self, v: I) -> Result<usize, I>
+    where
+        I: Ord,
+    {
+        let idx = match self[..].binary_search(&v) {
+            Ok(i) => i,
+            Err(i) => i,
+        };
+        self.append(v)?;
+        self[idx..].rotate_right(1);
+        Ok(idx)
+    }
+    /// Inline append an item (See Self::append)
+    pub fn appended(mut self, v: I) -> Result<Self, (Self, I)> {
+        match self.append(v) {
+            Ok(_) => Ok(self),
+            Err(v) => Err((self, v)),
+        }
+    }
+
+    /// Extend a slice with an iterator
+    pub fn extend<It: Iterator<Item = I>>(&mut self, mut iter: It) -> Result<(), (I, It)> {
+        while let Some(v) = iter.next() {
+            match self.append(v) {
+                Ok(_) => {}
+                Err(v) => return Err((v, iter)),
+            }
+        }
+        Ok(())
+    }
+    /// Helper to extend during construction (see Self::extend)
+    pub fn extended<It: Iterator<Item = I>>(mut self, iter: It) -> Result<Self, (Self, I, It)> {
+        match self.extend(iter) {
+            Ok(_) => Ok(self),
+            Err((v, iter)) => Err((self, v, iter)),
+        }
+    }
+
+    /// Remove the last item from the slice
+    pub fn pop(&mut self) -> Option<I> {
+        if !self.is_empty() {
+            let ofs = self.len() - 1;
+            let data = self.data.as_mut();
+            let info_words = D::round_to_words(mem::size_of::<usize>());
+            let info_ofs = data.len() - info_words;
+            unsafe {
+                crate::store_metadata(&mut data[info_ofs..], &[ofs]);
+                Some(ptr::read((data.as_ptr() as *const I).add(ofs)))
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Shorten the slice to `new_len` items, dropping the rest and returning how many were dropped
+    ///
+    /// Only rewrites the stored length (and drops the removed items), the buffer is left as-is.
+    /// Does nothing if `new_len` is not less than the current length.
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let mut v = Value::<[u8], stack_dst::buffers::Ptr8>::new_stable([1, 2, 3, 4], |v| v).unwrap();
+    /// assert_eq!(v.shrink_to(1), 3);
+    /// assert_eq!(&v[..], &[1]);
+    /// assert_eq!(v.shrink_to(5), 0);
+    /// ```
+    pub fn shrink_to(&mut self, new_len: usize) -> usize {
+        let len = self.len();
+        if new_len >= len {
+            return 0;
+        }
+        unsafe {
+            // Update the length first, so a panicking drop leaks the remaining items instead of
+            // them being dropped again
+            self.set_len(new_len);
+            let base = self.data.as_mut().as_mut_ptr() as *mut I;
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                base.add(new_len),
+                len - new_len,
+            ));
+        }
+        len - new_len
+    }
+
+    /// Split the slice in two at the given index, moving the tail into a new value
+    ///
+    /// Panics if `at` is greater than the length
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let mut v = Value::<[u8], stack_dst::buffers::Ptr8>::new_stable([1, 2, 3], |v| v).unwrap();
+    /// let w = v.split_off(1).unwrap();
+    /// assert_eq!(&v[..], &[1]);
+    /// assert_eq!(&w[..], &[2, 3]);
+    /// ```
+    pub fn split_off(&mut self, at: usize) -> Result<Self, ()>
+    where
+        D: Default,
+    {
+        let len = self.len();
+        assert!(
+            at <= len,
+            "`at` ({}) is past the end of the slice ({})",
+            at,
+            len
+        );
+        let count = len - at;
+
+        let mut rv = Self::empty_slice()?;
+        let info_words = D::round_to_words(mem::size_of::<usize>());
+        let req_words = D::round_to_words(count * mem::size_of::<I>()) + info_words;
+        if let Err(_) = crate::extend_buf(&mut rv.data, false, req_words) {
+            return Err(());
+        }
+        // SAFE: Space has been checked, and the moved items are removed from `self` before
+        // anything else can observe them
+        unsafe {
+            ptr::copy_nonoverlapping(
+                (**self).as_ptr().add(at),
+                rv.data.as_mut().as_mut_ptr() as *mut I,
+                count,
+            );
+            self.set_len(at);
+            rv.set_len(count);
+        }
+        Ok(rv)
+    }
+
+    /// Move the items out into a new value, leaving an empty slice in its place
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let mut v = Value::<[u8], stack_dst::buffers::Ptr8>::new_stable([1, 2, 3], |v| v).unwrap();
+    /// let w = v.take().unwrap();
+    /// assert_eq!(&v[..], &[]);
+    /// assert_eq!(&w[..], &[1, 2, 3]);
+    /// ```
+    pub fn take(&mut self) -> Result<Self, ()>
+    where
+        D: Default,
+    {
+        let empty = Self::empty_slice()?;
+        Ok(mem::replace(self, empty))
+    }
+
+    /// Overwrite every item with a clone of `value` (dropping the existing items first)
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let mut v = Value::<[u8], stack_dst::buffers::Ptr8>::new_stable([1, 2, 3], |v| v).unwrap();
+    /// v.fill(7);
+    /// assert_eq!(&v[..], &[7, 7, 7]);
+    /// ```
+    pub fn fill(&mut self, value: I)
+    where
+        I: Clone,
+    {
+        self.fill_with(|| value.clone())
+    }
+
+    /// Overwrite every item with the result of `f` (dropping the existing items first)
+    ///
+    /// If `f` panics, only the items written so far are kept.
+    pub fn fill_with<F: FnMut() -> I>(&mut self, mut f: F) {
+        let len = self.len();
+        // SAFE: The length is cleared before the items are dropped, and only increased once each
+        // new item has been written
+        unsafe {
+            self.set_len(0);
+            let base = self.data.as_mut().as_mut_ptr() as *mut I;
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(base, len));
+            for i in 0..len {
+                ptr::write(base.add(i), f());
+                self.set_len(i + 1);
+            }
+        }
+    }
+
+    /// Remove the items in `range`, returning them as an iterator (similar to `Vec::drain`)
+    ///
+    /// The items after the range are moved down once the iterator is dropped (any items not
+    /// yielded are dropped). If the iterator is leaked, the items after the start of the range are
+    /// also leaked.
+    ///
+    /// Panics if the range is out of bounds
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let mut v = Value::<[u8], stack_dst::buffers::Ptr8>::new_stable([1, 2, 3, 4], |v| v).unwrap();
+    /// assert_eq!(v.drain(1..3).collect::<Vec<_>>(), [2, 3]);
+    /// assert_eq!(&v[..], &[1, 4]);
+    /// ```
+    pub fn drain<R: ops::RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, I, D> {
+        let len = self.len();
+        let (start, end) = resolve_range(range, len);
+        // SAFE: Items from `start` are now owned by the `Drain` (so won't be dropped by `self`)
+        unsafe {
+            self.set_len(start);
+        }
+        Drain {
+            value: self,
+            start,
+            cur: start,
+            end,
+            tail_start: end,
+            tail_len: len - end,
+        }
+    }
+}
+// Not bounded on `AlignmentValid`, so `Drain` can use it
+impl<I, D: ::DataBuf> Value<[I], D> {
+    /// Update the stored length (similar to `Vec::set_len`)
+    ///
+    /// UNSAFE: The first `len` items must be initialised, and `len` must not exceed `capacity`.
+    /// Shrinking the length doesn't drop the removed items.
+    pub unsafe fn set_len(&mut self, len: usize) {
+        let info_words = D::round_to_words(mem::size_of::<usize>());
+        let data = self.data.as_mut();
+        let info_ofs = data.len() - info_words;
+        crate::store_metadata(&mut data[info_ofs..], &[len]);
+    }
+}
+/// Convert a range into `start..end` bounds within `len` (panicking if out of bounds)
+fn resolve_range<R: ops::RangeBounds<usize>>(range: R, len: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        ops::Bound::Included(&v) => v,
+        ops::Bound::Excluded(&v) => v.checked_add(1).expect("Range start overflowed"),
+        ops::Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        ops::Bound::Included(&v) => v.checked_add(1).expect("Range end overflowed"),
+        ops::Bound::Excluded(&v) => v,
+        ops::Bound::Unbounded => len,
+    };
+    assert!(
+        start <= end,
+        "Range start ({}) is after end ({})",
+        start,
+        end
+    );
+    assert!(
+        end <= len,
+        "Range end ({}) is past the end of the slice ({})",
+        end,
+        len
+    );
+    (start, end)
+}
+
+/// Iterator over items removed from a `Value<[I]>` (see `Value::drain`)
+pub struct Drain<'a, I: 'a, D: 'a + ::DataBuf> {
+    value: &'a mut Value<[I], D>,
+    /// Start of the drained range (where the tail is moved to)
+    start: usize,
+    /// Range of items not yet yielded
+    cur: usize,
+    end: usize,
+    /// Items after the drained range
+    tail_start: usize,
+    tail_len: usize,
+}
+impl<'a, I: 'a, D: 'a + ::DataBuf> Drain<'a, I, D> {
+    fn base(&mut self) -> *mut I {
+        self.value.data.as_mut().as_mut_ptr() as *mut I
+    }
+}
+impl<'a, I: 'a, D: 'a + ::DataBuf> Iterator for Drain<'a, I, D> {
+    type Item = I;
+    fn next(&mut self) -> Option<I> {
+        if self.cur == self.end {
+            None
+        } else {
+            self.cur += 1;
+            // SAFE: Item is within the drained range, and is only read once
+            Some(unsafe { ptr::read(self.base().add(self.cur - 1)) })
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.end - self.cur;
+        (n, Some(n))
+    }
+}
+impl<'a, I: 'a, D: 'a + ::DataBuf> DoubleEndedIterator for Drain<'a, I, D> {
+    fn next_back(&mut self) -> Option<I> {
+        if self.cur == self.end {
+            None
+        } else {
+            self.end -= 1;
+            // SAFE: Item is within the drained range, and is only read once
+            Some(unsafe { ptr::read(self.base().add(self.end)) })
+        }
+    }
+}
+impl<'a, I: 'a, D: 'a + ::DataBuf> ExactSizeIterator for Drain<'a, I, D> {}
+impl<'a, I: 'a, D: 'a + ::DataBuf> Drop for Drain<'a, I, D> {
+    fn drop(&mut self) {
+        /// Moves the tail down (even if dropping the remaining items panics)
+        struct MoveTail<'r, 'a: 'r, I: 'a, D: 'a + ::DataBuf>(&'r mut Drain<'a, I, D>);
+        impl<'r, 'a: 'r, I: 'a, D: 'a + ::DataBuf> Drop for MoveTail<'r, 'a, I, D> {
+            fn drop(&mut self) {
+                let d = &mut *self.0;
+                // SAFE: The tail items are initialised, and the destination is within the buffer
+                unsafe {
+                    let base = d.base();
+                    ptr::copy(base.add(d.tail_start), base.add(d.start), d.tail_len);
+                    d.value.set_len(d.start + d.tail_len);
+                }
+            }
+        }
+
+        let (cur, end) = (self.cur, self.end);
+        self.cur = end;
+        let guard = MoveTail(self);
+        // SAFE: These items haven't been yielded, and won't be read again
+        unsafe {
+            let base = guard.0.base();
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(base.add(cur), end - cur));
+        }
+    }
+}
+
+/// Specialisation for byte slices
+impl<D: ::DataBuf> Value<[u8], D> {
+    /// Read the first `size_of::<U>()` bytes as a `U` (without copying)
+    ///
+    /// Returns `None` if the slice is too short, or the data isn't sufficiently aligned for `U`
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let v = Value::<[u8], stack_dst::buffers::Ptr8>::new_stable([1, 0, 0, 0, 9], |v| v).unwrap();
+    /// assert_eq!(v.as_pod::<u32>().map(|&x| u32::from_le(x)), Some(1));
+    /// assert_eq!(v.as_pod::<u64>(), None);
+    /// ```
+    pub fn as_pod<U: ::Pod>(&self) -> Option<&U> {
+        let ptr = self[..].as_ptr();
+        if self.len() < mem::size_of::<U>() || ptr as usize % mem::align_of::<U>() != 0 {
+            None
+        } else {
+            // SAFE: Length and alignment checked, and `Pod` is valid for any bit pattern
+            Some(unsafe { &*(ptr as *const U) })
+        }
+    }
+}
+
+/// Fallible allocation for `Vec`-backed slices (requires the `try_reserve` feature)
+#[cfg(feature = "try_reserve")]
+#[clippy::msrv = "1.57"]
+impl<I, W: ::Pod> Value<[I], ::alloc::vec::Vec<mem::MaybeUninit<W>>>
+where
+    (I, W): crate::AlignmentValid,
+{
+    /// Ensure that there is space for at least `additional` more items, reporting allocation
+    /// failure instead of aborting (similar to `Vec::try_reserve`)
+    ///
+    /// On failure (either the allocator failing, or the requested size overflowing) the value is
+    /// left unchanged.
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let mut v = Value::<[u32], stack_dst::buffers::PtrVec>::empty_slice().unwrap();
+    /// v.try_reserve(100).unwrap();
+    /// assert!(v.capacity() >= 100);
+    /// assert!(v.try_reserve(usize::MAX).is_err());
+    /// assert_eq!(v.len(), 0);
+    /// ```
+    pub fn try_reserve(
+        &mut self,
+        additional: usize,
+    ) -> Result<(), ::alloc::collections::TryReserveError> {
+        let info_words = crate::round_to_words::<W>(mem::size_of::<usize>());
+
+        let len = self.len();
+        // An overflowing size is passed on as `usize::MAX`, so `Vec` reports it as an error
+        let req_words = len
+            .checked_add(additional)
+            .and_then(|n| n.checked_mul(mem::size_of::<I>()))
+            .and_then(|n| n.checked_add(mem::size_of::<W>() - 1))
+            .and_then(|n| (n / mem::size_of::<W>()).checked_add(info_words))
+            .unwrap_or(usize::MAX);
+        let cur_words = self.data.len();
+        if req_words <= cur_words {
+            return Ok(());
+        }
+        self.data.try_reserve(req_words - cur_words)?;
+        // The capacity is now sufficient, so this doesn't allocate
+        crate::extend_buf(&mut self.data, false, req_words).expect("BUG: Vec extend failed");
+        // SAFE: Length is unchanged, but the metadata slot moves to the new end of the buffer
+        unsafe {
+            self.set_len(len);
+        }
+        Ok(())
+    }
+}
+
+/// Helpers for storing futures
+///
+/// A `Value` holding a future is `!Unpin` (unless the future is), so it must be pinned before it
+/// can be polled. Once pinned the stored future never moves, as it lives in the `Value`'s buffer.
+impl<'a, O, D: ::DataBuf> Value<dyn future::Future<Output = O> + 'a, D> {
+    /// Store a future
+    ///
+    /// ```edition2018
+    /// # use stack_dst::Value;
+    /// # use std::future::Future;
+    /// let fut = Value::<dyn Future<Output = i32>, ::stack_dst::buffers::Ptr8>::from_future(async { 1 + 2 })
+    ///     .ok()
+    ///     .unwrap();
+    /// let mut fut = Box::pin(fut);
+    ///
+    /// # use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+    /// # fn noop_raw() -> RawWaker {
+    /// #     fn clone(_: *const ()) -> RawWaker { noop_raw() }
+    /// #     fn noop(_: *const ()) {}
+    /// #     static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+    /// #     RawWaker::new(std::ptr::null(), &VTABLE)
+    /// # }
+    /// let waker = unsafe { Waker::from_raw(noop_raw()) };
+    /// let mut cx = Context::from_waker(&waker);
+    /// assert_eq!(fut.as_mut().poll(&mut cx), Poll::Ready(3));
+    /// ```
+    pub fn from_future<F>(fut: F) -> Result<Self, F>
+    where
+        F: future::Future<Output = O> + 'a,
+        (F, D::Inner): crate::AlignmentValid,
+        D: Default,
+    {
+        Self::new_stable(fut, |p| p as _)
+    }
+
+    /// Store a future that calls the provided closure when polled (like `core::future::poll_fn`)
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// # use std::future::Future;
+    /// # use std::task::Poll;
+    /// let mut count = 0;
+    /// let fut = Value::<dyn Future<Output = i32>, ::stack_dst::buffers::Ptr8>::from_poll_fn(move |cx| {
+    ///     count += 1;
+    ///     if count < 3 {
+    ///         cx.waker().wake_by_ref();
+    ///         Poll::Pending
+    ///     } else {
+    ///         Poll::Ready(count)
+    ///     }
+    /// })
+    /// .ok()
+    /// .unwrap();
+    /// let mut fut = Box::pin(fut);
+    ///
+    /// # use std::task::{Context, RawWaker, RawWakerVTable, Waker};
+    /// # fn noop_raw() -> RawWaker {
+    /// #     fn clone(_: *const ()) -> RawWaker { noop_raw() }
+    /// #     fn noop(_: *const ()) {}
+    /// #     static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+    /// #     RawWaker::new(std::ptr::null(), &VTABLE)
+    /// # }
+    /// let waker = unsafe { Waker::from_raw(noop_raw()) };
+    /// let mut cx = Context::from_waker(&waker);
+    /// assert_eq!(fut.as_mut().poll(&mut cx), Poll::Pending);
+    /// assert_eq!(fut.as_mut().poll(&mut cx), Poll::Pending);
+    /// assert_eq!(fut.as_mut().poll(&mut cx), Poll::Ready(3));
+    /// ```
+    pub fn from_poll_fn<F>(f: F) -> Result<Self, F>
+    where
+        F: FnMut(&mut task::Context) -> task::Poll<O> + 'a,
+        (PollFn<F>, D::Inner): crate::AlignmentValid,
+        D: Default,
+    {
+        Self::from_future(PollFn(f)).map_err(|v| v.0)
+    }
+}
+/// A slice value with reserved space for items that haven't yet been initialised (similar to
+/// `Vec::with_capacity` followed by `set_len`)
+///
+/// The items can be written in any order through `as_mut_slice`, then `assume_init` converts to
+/// a `Value`. If this is dropped without calling `assume_init`, the items are leaked (not dropped).
+///
+/// ```
+/// # use stack_dst::value::ValueUninit;
+/// let mut v = ValueUninit::<[u32], ::stack_dst::buffers::Ptr8>::new(3).unwrap();
+/// for (i, slot) in v.as_mut_slice().iter_mut().enumerate() {
+///     *slot = std::mem::MaybeUninit::new(i as u32 * 10);
+/// }
+/// // SAFE: All items have been written
+/// let v = unsafe { v.assume_init() };
+/// assert_eq!(&v[..], &[0, 10, 20]);
+/// ```
+pub struct ValueUninit<T: ?Sized, D: ::DataBuf> {
+    /// Value with a length of zero (so doesn't drop any items)
+    value: Value<T, D>,
+    /// Number of reserved items
+    len: usize,
+}
+impl<I, D: ::DataBuf> ValueUninit<[I], D>
+where
+    (I, D::Inner): crate::AlignmentValid,
+{
+    /// Reserve space for `len` items (returns `Err` if they don't fit)
+    pub fn new(len: usize) -> Result<Self, ()>
+    where
+        D: Default,
+    {
+        Self::with_buffer(Default::default(), len)
+    }
+    /// Reserve space for `len` items in the provided buffer (returns `Err` if they don't fit)
+    pub fn with_buffer(buffer: D, len: usize) -> Result<Self, ()> {
+        let mut value = Value::empty_slice_with_buffer(buffer)?;
+        let bytes = len
+            .checked_mul(mem::size_of::<I>())
+            // No allocation can be larger than `isize::MAX`
+            .filter(|&n| n <= isize::MAX as usize)
+            .ok_or(())?;
+        let info_words = D::round_to_words(mem::size_of::<usize>());
+        let req_words = D::round_to_words(bytes).checked_add(info_words).ok_or(())?;
+        if let Err(_) = crate::extend_buf(&mut value.data, false, req_words) {
+            return Err(());
+        }
+        // SAFE: No items are populated, and the metadata slot may have moved during `extend`
+        unsafe {
+            value.set_len(0);
+        }
+        Ok(ValueUninit { value, len })
+    }
+}
+impl<I, D: ::DataBuf> ValueUninit<[I], D> {
+    /// Returns the number of reserved items
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Returns `true` if no items are reserved
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Get the (possibly uninitialised) items
+    pub fn as_mut_slice(&mut self) -> &mut [mem::MaybeUninit<I>] {
+        // SAFE: Space for `len` items was reserved, and `MaybeUninit` doesn't need initialising
+        unsafe {
+            let base = self.value.data.as_mut().as_mut_ptr() as *mut mem::MaybeUninit<I>;
+            core::slice::from_raw_parts_mut(base, self.len)
+        }
+    }
+    /// Convert into a `Value` containing the items
+    ///
+    /// UNSAFE: All of the items in `as_mut_slice` must have been initialised
+    pub unsafe fn assume_init(mut self) -> Value<[I], D> {
+        self.value.set_len(self.len);
+        self.value
+    }
+}
+
+/// Builder for a slice or string value, adding items with chained calls (from `Value::builder`)
+///
+/// ```
+/// # use stack_dst::Value;
+/// let v = Value::<[u8], ::stack_dst::buffers::Ptr8>::builder()
+///     .unwrap()
+///     .push(1)
+///     .and_then(|b| b.push(2))
+///     .and_then(|b| b.push(3))
+///     .unwrap()
+///     .build();
+/// assert_eq!(&v[..], &[1, 2, 3]);
+///
+/// let s = Value::<str, ::stack_dst::buffers::Ptr8>::builder()
+///     .unwrap()
+///     .push_str("Hello")
+///     .and_then(|b| b.push_str(", world"))
+///     .unwrap()
+///     .build();
+/// assert_eq!(&s[..], "Hello, world");
+/// ```
+pub struct ValueBuilder<T: ?Sized, D: ::DataBuf>(Value<T, D>);
+impl<I, D: ::DataBuf> ValueBuilder<[I], D> {
+    /// Append an item, returning it on failure (see `Value::append`)
+    pub fn push(mut self, v: I) -> Result<Self, I> {
+        self.0.append(v)?;
+        Ok(self)
+    }
+    /// Finish building, returning the value
+    pub fn build(self) -> Value<[I], D> {
+        self.0
+    }
+}
+impl<D: ::DataBuf> ValueBuilder<str, D> {
+    /// Append a string (see `Value::append_str`)
+    pub fn push_str(mut self, v: &str) -> Result<Self, ()> {
+        self.0.append_str(v)?;
+        Ok(self)
+    }
+    /// Append a character, returning it on failure
+    pub fn push(self, c: char) -> Result<Self, char> {
+        self.push_str(c.encode_utf8(&mut [0; 4])).map_err(|_| c)
+    }
+    /// Finish building, returning the value
+    pub fn build(self) -> Value<str, D> {
+        self.0
+    }
+}
+
+/// Helpers for storing errors (requires the `core_error` feature)
+#[cfg(feature = "core_error")]
+impl<'a, D: ::DataBuf> Value<dyn core::error::Error + 'a, D> {
+    /// Store an error
+    ///
     /// ```
-    pub fn truncate(&mut self, len: usize) {
-        if len < self.len() {
-            let _ = &self[..][len..]; // Index to force a panic if the index isn't char-aligned
+    /// # use stack_dst::Value;
+    /// let e = Value::<dyn core::error::Error, ::stack_dst::buffers::Ptr8>::from_error(
+    ///     "x".parse::<u32>().unwrap_err(),
+    /// )
+    /// .unwrap();
+    /// assert_eq!(e.to_string(), "invalid digit found in string");
+    /// ```
+    pub fn from_error<E>(e: E) -> Result<Self, E>
+    where
+        E: core::error::Error + 'a,
+        (E, D::Inner): crate::AlignmentValid,
+        D: Default,
+    {
+        Self::new_stable(e, |v| v as _)
+    }
 
-            let info_words = D::round_to_words(mem::size_of::<usize>());
-            let data = self.data.as_mut();
-            let info_ofs = data.len() - info_words;
-            crate::store_metadata(&mut data[info_ofs..], &[len]);
+    /// Iterate over the chain of errors that caused this one (starting with `source()`, so not
+    /// including this error)
+    pub fn sources(&self) -> Sources<'_> {
+        Sources {
+            next: (**self).source(),
         }
     }
 }
-/// Specialisation for slices (acting like an `ArrayVec`)
-impl<I, D: ::DataBuf> Value<[I], D>
-where
-    (I, D::Inner): crate::AlignmentValid,
+/// Iterator over the chain of error sources (see `Value::sources`)
+#[cfg(feature = "core_error")]
+pub struct Sources<'a> {
+    next: Option<&'a (dyn core::error::Error + 'static)>,
+}
+#[cfg(feature = "core_error")]
+impl<'a> Iterator for Sources<'a> {
+    type Item = &'a (dyn core::error::Error + 'static);
+    fn next(&mut self) -> Option<Self::Item> {
+        let rv = self.next.take()?;
+        self.next = rv.source();
+        Some(rv)
+    }
+}
+
+/// Either an inline value or a boxed fallback (see `Value::new_str_or_boxed`)
+///
+/// Both dereference to the same type, so this can be used without checking which it is.
+#[derive(Debug)]
+pub enum InlineOrBoxed<I, B> {
+    /// Stored inline (in a `Value`)
+    Inline(I),
+    /// Too large to store inline, so stored on the heap
+    Boxed(B),
+}
+impl<T: ?Sized, I: ops::Deref<Target = T>, B: ops::Deref<Target = T>> ops::Deref
+    for InlineOrBoxed<I, B>
 {
-    /// Create a new zero-sized slice (will error only if the metadata doesn't fit)
-    pub fn empty_slice() -> Result<Self, ()>
+    type Target = T;
+    fn deref(&self) -> &T {
+        match *self {
+            InlineOrBoxed::Inline(ref v) => v,
+            InlineOrBoxed::Boxed(ref v) => v,
+        }
+    }
+}
+impl<T: ?Sized, I: ops::DerefMut<Target = T>, B: ops::DerefMut<Target = T>> ops::DerefMut
+    for InlineOrBoxed<I, B>
+{
+    fn deref_mut(&mut self) -> &mut T {
+        match *self {
+            InlineOrBoxed::Inline(ref mut v) => v,
+            InlineOrBoxed::Boxed(ref mut v) => v,
+        }
+    }
+}
+
+/// Future that calls a closure when polled (see `Value::from_poll_fn`)
+pub struct PollFn<F>(F);
+// The closure is never pinned
+impl<F> Unpin for PollFn<F> {}
+impl<O, F: FnMut(&mut task::Context) -> task::Poll<O>> future::Future for PollFn<F> {
+    type Output = O;
+    fn poll(self: pin::Pin<&mut Self>, cx: &mut task::Context) -> task::Poll<O> {
+        (self.get_mut().0)(cx)
+    }
+}
+
+/// Named forwards of common slice methods (for parity with `Vec`)
+impl<I, D: ::DataBuf> Value<[I], D> {
+    /// Sort the slice in place (stable, see `slice::sort`)
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let mut v = Value::<[i32], stack_dst::buffers::Ptr8>::new_stable([3, 1, 2], |v| v).unwrap();
+    /// v.sort();
+    /// assert_eq!(&v[..], &[1, 2, 3]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn sort(&mut self)
     where
-        D: Default,
+        I: Ord,
     {
-        Self::empty_slice_with_buffer(Default::default())
+        (**self).sort()
     }
-    /// Create a new zero-sized slice in the provided buffer (will error only if the metadata doesn't fit)
-    pub fn empty_slice_with_buffer(mut buffer: D) -> Result<Self, ()> {
-        <(I, D::Inner) as crate::AlignmentValid>::check();
-
-        let info_words = D::round_to_words(mem::size_of::<usize>());
-        let req_words = info_words;
-        if let Err(_) = buffer.extend(req_words) {
-            return Err(());
+    /// Sort the slice in place using a comparator (stable, see `slice::sort_by`)
+    #[cfg(feature = "alloc")]
+    pub fn sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&I, &I) -> ::core::cmp::Ordering,
+    {
+        (**self).sort_by(compare)
+    }
+    /// Sort the slice in place (unstable, see `slice::sort_unstable`)
+    pub fn sort_unstable(&mut self)
+    where
+        I: Ord,
+    {
+        (**self).sort_unstable()
+    }
+    /// Sort the slice in place using a comparator (unstable, see `slice::sort_unstable_by`)
+    pub fn sort_unstable_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&I, &I) -> ::core::cmp::Ordering,
+    {
+        (**self).sort_unstable_by(compare)
+    }
+    /// Binary search a sorted slice for an item (see `slice::binary_search`)
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let v = Value::<[i32], stack_dst::buffers::Ptr8>::new_stable([1, 3, 5], |v| v).unwrap();
+    /// assert_eq!(v.binary_search(&3), Ok(1));
+    /// assert_eq!(v.binary_search(&4), Err(2));
+    /// ```
+    pub fn binary_search(&self, x: &I) -> Result<usize, usize>
+    where
+        I: Ord,
+    {
+        (**self).binary_search(x)
+    }
+    /// Binary search a sorted slice using a comparator (see `slice::binary_search_by`)
+    pub fn binary_search_by<F>(&self, f: F) -> Result<usize, usize>
+    where
+        F: FnMut(&I) -> ::core::cmp::Ordering,
+    {
+        (**self).binary_search_by(f)
+    }
+    /// Rotate the slice in place so the item at `mid` becomes the first (see `slice::rotate_left`)
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let mut v = Value::<[i32], stack_dst::buffers::Ptr8>::new_stable([1, 2, 3, 4], |v| v).unwrap();
+    /// v.rotate_left(1);
+    /// assert_eq!(&v[..], &[2, 3, 4, 1]);
+    /// ```
+    pub fn rotate_left(&mut self, mid: usize) {
+        (**self).rotate_left(mid)
+    }
+    /// Rotate the slice in place so the last `k` items move to the front (see `slice::rotate_right`)
+    pub fn rotate_right(&mut self, k: usize) {
+        (**self).rotate_right(k)
+    }
+    /// Split the slice into two at `mid` (see `slice::split_at`)
+    ///
+    /// Panics if `mid` is greater than the length
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let v = Value::<[u8], stack_dst::buffers::Ptr8>::new_stable([1, 2, 3], |v| v).unwrap();
+    /// let (a, b) = v.split_at(1);
+    /// assert_eq!(a, &[1]);
+    /// assert_eq!(b, &[2, 3]);
+    /// ```
+    pub fn split_at(&self, mid: usize) -> (&[I], &[I]) {
+        (**self).split_at(mid)
+    }
+    /// Split the slice into two mutable halves at `mid` (see `slice::split_at_mut`)
+    pub fn split_at_mut(&mut self, mid: usize) -> (&mut [I], &mut [I]) {
+        (**self).split_at_mut(mid)
+    }
+    /// Iterate over `size`-item chunks, with the last one possibly shorter (see `slice::chunks`)
+    ///
+    /// Panics if `size` is zero
+    pub fn chunks(&self, size: usize) -> ::core::slice::Chunks<'_, I> {
+        (**self).chunks(size)
+    }
+    /// Iterate over all overlapping `size`-item windows (see `slice::windows`)
+    ///
+    /// Panics if `size` is zero
+    pub fn windows(&self, size: usize) -> ::core::slice::Windows<'_, I> {
+        (**self).windows(size)
+    }
+    /// Split the slice into `N`-item arrays and a remainder (see `slice::as_chunks`)
+    ///
+    /// Panics if `N` is zero
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let v = Value::<[u8], stack_dst::buffers::Ptr8>::new_stable([1, 2, 3, 4, 5], |v| v).unwrap();
+    /// let (chunks, rem) = v.as_chunks::<2>();
+    /// assert_eq!(chunks, &[[1, 2], [3, 4]]);
+    /// assert_eq!(rem, &[5]);
+    /// ```
+    #[cfg(feature = "const_generics")]
+    pub fn as_chunks<const N: usize>(&self) -> (&[[I; N]], &[I]) {
+        assert!(N != 0, "chunk size must be non-zero");
+        let n_chunks = self.len() / N;
+        let (head, rem) = self.split_at(n_chunks * N);
+        // SAFE: `[I; N]` has the same layout as `N` consecutive `I`s, and `head` holds exactly
+        // `n_chunks * N` items
+        let chunks =
+            unsafe { ::core::slice::from_raw_parts(head.as_ptr() as *const [I; N], n_chunks) };
+        (chunks, rem)
+    }
+    /// Borrow the contents as a fixed-size array, if the length is exactly `N`
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// let v = Value::<[u8], stack_dst::buffers::Ptr8>::new_stable([1, 2, 3], |v| v).unwrap();
+    /// assert_eq!(v.as_array::<3>(), Some(&[1, 2, 3]));
+    /// assert_eq!(v.as_array::<4>(), None);
+    /// ```
+    #[cfg(feature = "const_generics")]
+    pub fn as_array<const N: usize>(&self) -> Option<&[I; N]> {
+        if self.len() == N {
+            // SAFE: `[I; N]` has the same layout as `N` consecutive `I`s, and the length matches
+            Some(unsafe { &*(self.as_ptr() as *const [I; N]) })
+        } else {
+            None
         }
-        assert!(req_words <= buffer.as_ref().len());
-
-        let mut rv = Value {
-            _pd: marker::PhantomData,
-            data: buffer,
-        };
+    }
+}
+impl<T: ?Sized, D: ::DataBuf> ops::Deref for Value<T, D> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.as_ptr() }
+    }
+}
+impl<T: ?Sized, D: ::DataBuf> ops::DerefMut for Value<T, D> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.as_ptr_mut() }
+    }
+}
+impl<T: ?Sized, D: ::DataBuf> ops::Drop for Value<T, D> {
+    fn drop(&mut self) {
+        unsafe { ptr::drop_in_place(&mut **self) }
+    }
+}
+/// Appends to the string, returning `fmt::Error` if it doesn't fit (the string may have been
+/// partially written)
+///
+/// ```
+/// # use stack_dst::Value;
+/// use std::fmt::Write;
+/// let mut s = Value::<str, stack_dst::buffers::Ptr8>::new_str("x = ").unwrap();
+/// write!(s, "{}", 42).unwrap();
+/// assert_eq!(&s[..], "x = 42");
+/// ```
+impl<D: ::DataBuf> fmt::Write for Value<str, D> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.append_str(s).map_err(|_| fmt::Error)
+    }
+}
+/// Compares the items with a slice
+///
+/// ```
+/// # use stack_dst::Value;
+/// let v = Value::<[i32], ::stack_dst::buffers::Ptr8>::new_stable([1, 2, 3], |p| p).unwrap();
+/// assert!(v == &[1, 2, 3][..]);
+/// assert!(v != &[1, 2][..]);
+/// ```
+impl<I: PartialEq, D: ::DataBuf> PartialEq<[I]> for Value<[I], D> {
+    fn eq(&self, other: &[I]) -> bool {
+        **self == *other
+    }
+}
+impl<'a, I: PartialEq, D: ::DataBuf> PartialEq<&'a [I]> for Value<[I], D> {
+    fn eq(&self, other: &&'a [I]) -> bool {
+        **self == **other
+    }
+}
+/// Compares the items with an array
+///
+/// ```
+/// # use stack_dst::Value;
+/// let v = Value::<[i32], ::stack_dst::buffers::Ptr8>::new_stable([1, 2, 3], |p| p).unwrap();
+/// assert_eq!(v, [1, 2, 3]);
+/// assert_ne!(v, [3, 2, 1]);
+/// ```
+#[cfg(feature = "const_generics")]
+impl<I: PartialEq, D: ::DataBuf, const N: usize> PartialEq<[I; N]> for Value<[I], D> {
+    fn eq(&self, other: &[I; N]) -> bool {
+        **self == other[..]
+    }
+}
+/// An empty string
+///
+/// Panics if the buffer cannot hold the metadata (see `empty_str`)
+impl<D: ::DataBuf + Default> Default for Value<str, D> {
+    fn default() -> Self {
+        Self::empty_str().expect("Value::<str>::default - Buffer too small for metadata")
+    }
+}
+/// An empty slice
+///
+/// Panics if the buffer cannot hold the metadata (see `empty_slice`)
+impl<I, D: ::DataBuf + Default> Default for Value<[I], D>
+where
+    (I, D::Inner): crate::AlignmentValid,
+{
+    fn default() -> Self {
+        Self::empty_slice().expect("Value::<[T]>::default - Buffer too small for metadata")
+    }
+}
 
-        let data = rv.data.as_mut();
-        let info_ofs = data.len() - info_words;
-        let (_data_dst, info_dst) = data.split_at_mut(info_ofs);
+/// Number of words used to store a `TypeId` (in a `TypedValue`)
+const TYPE_ID_WORDS: usize =
+    (mem::size_of::<any::TypeId>() + mem::size_of::<usize>() - 1) / mem::size_of::<usize>();
 
-        crate::store_metadata(info_dst, &[0]);
-        Ok(rv)
+fn type_id_words(id: any::TypeId) -> [usize; TYPE_ID_WORDS] {
+    let mut rv = [0; TYPE_ID_WORDS];
+    // SAFE: `rv` is at least as large as a `TypeId`
+    unsafe {
+        ptr::copy_nonoverlapping(
+            &id as *const _ as *const u8,
+            rv.as_mut_ptr() as *mut u8,
+            mem::size_of::<any::TypeId>(),
+        );
     }
+    rv
+}
 
-    /// Append an item to the end of the slice (similar to `Vec::push`)
-    pub fn append(&mut self, v: I) -> Result<(), I> {
-        let info_words = D::round_to_words(mem::size_of::<usize>());
+/// Number of `usize`s taken by `words` words of info once padded to a whole number of `D` words
+fn padded_info_words<D: ::DataBuf>(words: usize) -> usize {
+    let bytes = D::round_to_words(words * mem::size_of::<usize>()) * mem::size_of::<D::Inner>();
+    (bytes + mem::size_of::<usize>() - 1) / mem::size_of::<usize>()
+}
 
-        let ofs = self.len();
+/// Function (stored in a `TypedValue`) that converts a pointer to the stored value into `dyn Any`
+type AnyPtrFn = fn(*mut ()) -> *mut dyn any::Any;
+fn any_ptr<U: 'static>(p: *mut ()) -> *mut dyn any::Any {
+    p as *mut U
+}
 
-        // Check/expand sufficient space
-        let req_words = D::round_to_words((ofs + 1) * mem::size_of::<I>()) + info_words;
-        if let Err(_) = self.data.extend(req_words) {
-            return Err(v);
-        }
-        let data = self.data.as_mut();
-        assert!(req_words <= data.len());
-        // Write the new value
-        // SAFE: Alignment is checked, pointer is in-bounds
+/// A `Value` that also records the `TypeId` of the stored value, allowing it to be downcast
+/// (even if `T` isn't `dyn Any`)
+///
+/// Constructed using `Value::new_stable_typed`
+pub struct TypedValue<T: ?Sized, D: ::DataBuf>(Value<T, D>);
+impl<T: ?Sized, D: ::DataBuf> TypedValue<T, D> {
+    /// Offset (in words) of the upcast function, the type ID is immediately before it
+    fn any_fn_ofs(&self) -> usize {
+        let info_words = D::round_to_words(mem::size_of::<&T>() - mem::size_of::<usize>());
+        let fn_words = D::round_to_words(mem::size_of::<usize>());
+        self.0.data.as_ref().len() - info_words - fn_words
+    }
+    /// Get the `TypeId` of the stored value
+    pub fn stored_type_id(&self) -> any::TypeId {
+        let data = self.0.data.as_ref();
+        let id_words = D::round_to_words(TYPE_ID_WORDS * mem::size_of::<usize>());
+        let ofs = self.any_fn_ofs() - id_words;
+        // SAFE: The type ID is stored before the upcast function, by `new_stable_typed`
+        unsafe { ptr::read_unaligned(data[ofs..].as_ptr() as *const any::TypeId) }
+    }
+    fn any_fn(&self) -> AnyPtrFn {
+        let data = self.0.data.as_ref();
+        // SAFE: The function is stored immediately before the metadata, by `new_stable_typed`
         unsafe {
-            let data_ptr = (data.as_ptr() as *mut I).add(ofs);
-            ptr::write(data_ptr, v);
+            let f = ptr::read_unaligned(data[self.any_fn_ofs()..].as_ptr() as *const usize);
+            mem::transmute::<usize, AnyPtrFn>(f)
         }
-        // Only update item count after the write
-        let info_ofs = data.len() - info_words;
-        crate::store_metadata(&mut data[info_ofs..], &[ofs + 1]);
-
-        Ok(())
     }
-    /// Inline append an item (See Self::append)
-    pub fn appended(mut self, v: I) -> Result<Self, (Self, I)> {
-        match self.append(v) {
-            Ok(_) => Ok(self),
-            Err(v) => Err((self, v)),
-        }
+    /// Upcast the stored value to `dyn Any` (even if `T` doesn't have `Any` as a supertrait)
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// # use std::fmt::Display;
+    /// let val = Value::<dyn Display, ::stack_dst::buffers::Ptr8>::new_stable_typed(1234u32, |v| v as _)
+    ///     .expect("Insufficient size");
+    /// let any = val.as_any();
+    /// assert_eq!(any.downcast_ref::<u32>(), Some(&1234));
+    /// ```
+    pub fn as_any(&self) -> &dyn any::Any {
+        // SAFE: The function was created for the stored type
+        unsafe { &*self.any_fn()(self.0.as_ptr() as *const () as *mut ()) }
     }
-
-    /// Extend a slice with an iterator
-    pub fn extend<It: Iterator<Item = I>>(&mut self, mut iter: It) -> Result<(), (I, It)> {
-        while let Some(v) = iter.next() {
-            match self.append(v) {
-                Ok(_) => {}
-                Err(v) => return Err((v, iter)),
-            }
+    /// Upcast the stored value to a mutable `dyn Any`
+    pub fn as_any_mut(&mut self) -> &mut dyn any::Any {
+        let f = self.any_fn();
+        // SAFE: The function was created for the stored type
+        unsafe { &mut *f(self.0.as_ptr_mut() as *mut ()) }
+    }
+    /// Returns `true` if the stored value is a `U`
+    pub fn is<U: 'static>(&self) -> bool {
+        self.stored_type_id() == any::TypeId::of::<U>()
+    }
+    /// Get a reference to the stored value, if it is a `U`
+    pub fn downcast_ref_typed<U: 'static>(&self) -> Option<&U> {
+        if self.is::<U>() {
+            // SAFE: Type checked above
+            Some(unsafe { &*(self.0.as_ptr() as *const U) })
+        } else {
+            None
         }
-        Ok(())
     }
-    /// Helper to extend during construction (see Self::extend)
-    pub fn extended<It: Iterator<Item = I>>(mut self, iter: It) -> Result<Self, (Self, I, It)> {
-        match self.extend(iter) {
-            Ok(_) => Ok(self),
-            Err((v, iter)) => Err((self, v, iter)),
+    /// Get a mutable reference to the stored value, if it is a `U`
+    pub fn downcast_mut_typed<U: 'static>(&mut self) -> Option<&mut U> {
+        if self.is::<U>() {
+            // SAFE: Type checked above
+            Some(unsafe { &mut *(self.0.as_ptr_mut() as *mut U) })
+        } else {
+            None
         }
     }
-
-    /// Remove the last item from the slice
-    pub fn pop(&mut self) -> Option<I> {
-        if self.len() > 0 {
-            let ofs = self.len() - 1;
-            let data = self.data.as_mut();
-            let info_words = D::round_to_words(mem::size_of::<usize>());
-            let info_ofs = data.len() - info_words;
+    /// Move the stored value out, if it is a `U`
+    ///
+    /// ```
+    /// # use stack_dst::Value;
+    /// # use std::fmt::Debug;
+    /// let val = Value::<dyn Debug, ::stack_dst::buffers::Ptr8>::new_stable_typed(String::from("Hi"), |v| v as _)
+    ///     .unwrap();
+    /// let val = val.downcast::<u32>().unwrap_err();
+    /// assert_eq!(val.downcast::<String>().ok(), Some(String::from("Hi")));
+    /// ```
+    pub fn downcast<U: 'static>(self) -> Result<U, Self> {
+        if self.is::<U>() {
+            let this = mem::ManuallyDrop::new(self);
+            // SAFE: Type checked above, and the value isn't dropped (only the buffer is)
             unsafe {
-                crate::store_metadata(&mut data[info_ofs..], &[ofs]);
-                Some(ptr::read((data.as_ptr() as *const I).add(ofs)))
+                let rv = ptr::read(this.0.as_ptr() as *const U);
+                drop(ptr::read(&this.0.data));
+                Ok(rv)
             }
         } else {
-            None
+            Err(self)
         }
     }
+    /// Discard the type information, returning a plain `Value`
+    pub fn into_value(self) -> Value<T, D> {
+        self.0
+    }
 }
-impl<T: ?Sized, D: ::DataBuf> ops::Deref for Value<T, D> {
+impl<T: ?Sized, D: ::DataBuf> ops::Deref for TypedValue<T, D> {
     type Target = T;
     fn deref(&self) -> &T {
-        unsafe { &*self.as_ptr() }
+        &self.0
     }
 }
-impl<T: ?Sized, D: ::DataBuf> ops::DerefMut for Value<T, D> {
+impl<T: ?Sized, D: ::DataBuf> ops::DerefMut for TypedValue<T, D> {
     fn deref_mut(&mut self) -> &mut T {
-        unsafe { &mut *self.as_ptr_mut() }
+        &mut self.0
     }
 }
-impl<T: ?Sized, D: ::DataBuf> ops::Drop for Value<T, D> {
-    fn drop(&mut self) {
-        unsafe { ptr::drop_in_place(&mut **self) }
+
+/// A borrowed DST, usable where an owned `Value` is (via `traits::ValueView`) without copying
+///
+/// ```
+/// # use stack_dst::ValueRef;
+/// let bytes = [1u8, 2, 3];
+/// let v = ValueRef::new(&bytes[..]);
+/// assert_eq!(v.len(), 3);
+/// ```
+#[derive(Debug)]
+pub struct ValueRef<'a, T: 'a + ?Sized>(&'a T);
+impl<'a, T: 'a + ?Sized> ValueRef<'a, T> {
+    /// Wrap a reference
+    pub fn new(v: &'a T) -> Self {
+        ValueRef(v)
+    }
+    /// Obtain the wrapped reference
+    pub fn into_inner(self) -> &'a T {
+        self.0
+    }
+}
+impl<'a, T: 'a + ?Sized> Clone for ValueRef<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<'a, T: 'a + ?Sized> Copy for ValueRef<'a, T> {}
+impl<'a, T: 'a + ?Sized> ops::Deref for ValueRef<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.0
     }
 }
 
@@ -13,10 +13,32 @@ macro_rules! d {
     }
 }
 
-d! { future::Future;
+/// Polls the stored future
+///
+/// The future is structurally pinned: it is stored inline, so it moves whenever the `Value`
+/// moves. This is sound because the `Value` is only `Unpin` if `T` is (e.g. `dyn Future` isn't),
+/// so for `!Unpin` futures the `Value` itself must be pinned before it can be polled, and then
+/// can't move (or have its buffer reallocated) until it's dropped.
+///
+/// ```compile_fail,edition2018
+/// # use stack_dst::Value;
+/// # use std::future::Future;
+/// # use std::pin::Pin;
+/// # fn check(cx: &mut std::task::Context) {
+/// let mut fut = Value::<dyn Future<Output = ()>, ::stack_dst::buffers::Ptr8>::from_future(async {})
+///     .ok()
+///     .unwrap();
+/// // Error: `Value<dyn Future>` is `!Unpin`, so must be pinned in place (e.g. `pin!`)
+/// let _ = Pin::new(&mut fut).poll(cx);
+/// # }
+/// ```
+impl<D: ::DataBuf, T: ?Sized> future::Future for super::Value<T, D>
+where
+    T: future::Future,
+{
     type Output = T::Output;
     fn poll(self: pin::Pin<&mut Self>, cx: &mut task::Context) -> task::Poll<Self::Output> {
-        unsafe { pin::Pin::new_unchecked(&mut **self.get_unchecked_mut()).poll(cx) }
+        self.as_pin_mut().poll(cx)
     }
 }
 d! { ::core::iter::Iterator;
@@ -54,6 +76,42 @@ d! { ::core::iter::ExactSizeIterator;
     //fn is_empty(&self) -> bool { (**self).is_empty() }
 }
 
+/// Forwards indexing to the stored value (e.g. a `dyn Index` map, or slicing a `[T]`/`str`)
+///
+/// ```
+/// # use stack_dst::Value;
+/// # use std::ops::Index;
+/// struct Squares;
+/// impl Index<u8> for Squares {
+///     type Output = u16;
+///     fn index(&self, k: u8) -> &u16 {
+///         static TABLE: [u16; 4] = [0, 1, 4, 9];
+///         &TABLE[k as usize]
+///     }
+/// }
+/// let v = Value::<dyn Index<u8, Output = u16>, ::stack_dst::buffers::Ptr2>::new_stable(Squares, |p| p as _)
+///     .ok()
+///     .unwrap();
+/// assert_eq!(v[3], 9);
+/// ```
+impl<K, D: ::DataBuf, T: ?Sized> ::core::ops::Index<K> for super::Value<T, D>
+where
+    T: ::core::ops::Index<K>,
+{
+    type Output = T::Output;
+    fn index(&self, key: K) -> &Self::Output {
+        (**self).index(key)
+    }
+}
+impl<K, D: ::DataBuf, T: ?Sized> ::core::ops::IndexMut<K> for super::Value<T, D>
+where
+    T: ::core::ops::IndexMut<K>,
+{
+    fn index_mut(&mut self, key: K) -> &mut Self::Output {
+        (**self).index_mut(key)
+    }
+}
+
 macro_rules! impl_fmt {
     ( $( $t:ident )* ) => {
         $(
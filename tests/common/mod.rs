@@ -0,0 +1,39 @@
+//! Buffer fixtures shared between the test crates
+
+/// A growable buffer that always fails to allocate
+#[derive(Default)]
+pub struct FailingAlloc;
+unsafe impl stack_dst::DataBuf for FailingAlloc {
+    type Inner = usize;
+    fn as_ref(&self) -> &[::std::mem::MaybeUninit<usize>] {
+        &[]
+    }
+    fn as_mut(&mut self) -> &mut [::std::mem::MaybeUninit<usize>] {
+        &mut []
+    }
+    fn extend(&mut self, _len: usize) -> Result<(), ()> {
+        Err(())
+    }
+}
+
+/// A (deliberately broken) buffer that can be shrunk without the container knowing
+#[cfg(all(debug_assertions, feature = "alloc"))]
+pub struct ShrinkableBuf(
+    pub Vec<std::mem::MaybeUninit<usize>>,
+    pub std::rc::Rc<std::cell::Cell<usize>>,
+);
+#[cfg(all(debug_assertions, feature = "alloc"))]
+unsafe impl stack_dst::DataBuf for ShrinkableBuf {
+    type Inner = usize;
+    fn as_ref(&self) -> &[std::mem::MaybeUninit<usize>] {
+        let len = std::cmp::min(self.0.len(), self.1.get());
+        &self.0[..len]
+    }
+    fn as_mut(&mut self) -> &mut [std::mem::MaybeUninit<usize>] {
+        let len = std::cmp::min(self.0.len(), self.1.get());
+        &mut self.0[..len]
+    }
+    fn extend(&mut self, len: usize) -> Result<(), ()> {
+        stack_dst::DataBuf::extend(&mut self.0, len)
+    }
+}
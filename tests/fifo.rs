@@ -1,5 +1,10 @@
 extern crate stack_dst;
 
+mod common;
+use common::FailingAlloc;
+#[cfg(all(debug_assertions, feature = "alloc"))]
+use common::ShrinkableBuf;
+
 type DstFifo<T> = stack_dst::Fifo<T, ::stack_dst::buffers::Ptr8>;
 
 #[test]
@@ -84,38 +89,643 @@ fn retain() {
 mod unaligned {
     use stack_dst::Fifo;
     use std::any::Any;
-    type Buf8_16 = ::stack_dst::buffers::ArrayBuf<u8, ::stack_dst::buffers::n::U16>;
+    type BufU8 = ::stack_dst::buffers::U8_32;
     #[test]
     #[should_panic]
     fn push_back_stable() {
-        let mut stack = Fifo::<dyn Any, Buf8_16>::new();
+        let mut stack = Fifo::<dyn Any, BufU8>::new();
         let _ = stack.push_back_stable(123u32, |v| v as _);
     }
     #[test]
+    fn try_push_back_stable() {
+        let mut queue = Fifo::<dyn Any, BufU8>::new();
+        let e = queue.try_push_back_stable(123u32, |v| v as _).unwrap_err();
+        assert_eq!(e.kind(), ::stack_dst::PushError::Misaligned);
+        assert_eq!(e.into_inner(), 123);
+        assert!(queue.is_empty());
+        queue.try_push_back_stable(1u8, |v| v as _).unwrap();
+    }
+    #[test]
     #[should_panic]
     #[cfg(feature = "unsize")]
     fn push_back() {
-        let mut stack = Fifo::<dyn Any, Buf8_16>::new();
+        let mut stack = Fifo::<dyn Any, BufU8>::new();
         let _ = stack.push_back(123u32);
     }
 
     #[test]
     #[should_panic]
     fn push_cloned() {
-        let mut stack = Fifo::<[u32], Buf8_16>::new();
+        let mut stack = Fifo::<[u32], BufU8>::new();
         let _ = stack.push_cloned(&[123u32]);
     }
 
     #[test]
     #[should_panic]
     fn push_copied() {
-        let mut stack = Fifo::<[u32], Buf8_16>::new();
+        let mut stack = Fifo::<[u32], BufU8>::new();
         let _ = stack.push_copied(&[123u32]);
     }
     #[test]
     #[should_panic]
     fn push_from_iter() {
-        let mut stack = Fifo::<[u32], Buf8_16>::new();
+        let mut stack = Fifo::<[u32], BufU8>::new();
         let _ = stack.push_from_iter(0..1);
     }
 }
+
+#[test]
+fn push_errors() {
+    use stack_dst::PushError;
+    use std::any::Any;
+    let mut list = stack_dst::Fifo::<dyn Any, ::stack_dst::buffers::Ptr2>::new();
+    assert_eq!(
+        list.try_push_back_stable([0usize; 2], |p| p)
+            .unwrap_err()
+            .kind(),
+        PushError::TooLarge
+    );
+    list.try_push_back_stable(1usize, |p| p).unwrap();
+    assert_eq!(
+        list.try_push_back_stable(2usize, |p| p).unwrap_err().kind(),
+        PushError::Full
+    );
+    // Once popped, the same push succeeds
+    list.pop_front();
+    list.try_push_back_stable(2usize, |p| p).unwrap();
+
+    let mut list = stack_dst::Fifo::<str, FailingAlloc>::new();
+    assert_eq!(list.try_push_back_str("Hello"), Err(PushError::AllocFailed));
+}
+
+#[test]
+fn iter_len() {
+    let mut list = DstFifo::<str>::new();
+    list.push_back_str("Hello").unwrap();
+    list.push_back_str("").unwrap();
+    list.push_back_str("World").unwrap();
+    assert_eq!(list.len(), 3);
+    assert_eq!(list.iter().len(), 3);
+    let mut it = list.iter_mut();
+    assert_eq!(it.size_hint(), (3, Some(3)));
+    it.next();
+    assert_eq!(it.size_hint(), (2, Some(2)));
+    list.pop_front();
+    list.retain(|v| !v.is_empty());
+    assert_eq!(list.len(), 1);
+    assert_eq!(list.iter().len(), 1);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn clone_from() {
+    let mut src = stack_dst::Fifo::<[String], ::stack_dst::buffers::PtrVec>::new();
+    src.push_cloned(&["a".to_owned(), "b".to_owned()]).unwrap();
+    src.push_cloned(&["c".to_owned()]).unwrap();
+
+    let mut dst = src.clone();
+    dst.push_cloned(&vec!["x".to_owned(); 8]).unwrap();
+    dst.pop_front();
+    dst.clone_from(&src);
+    assert_eq!(dst.len(), 2);
+    let mut it = dst.iter();
+    assert_eq!(it.next(), Some(&["a".to_owned(), "b".to_owned()][..]));
+    assert_eq!(it.next(), Some(&["c".to_owned()][..]));
+    assert_eq!(it.next(), None);
+}
+
+#[test]
+fn from_strs() {
+    let list = DstFifo::<str>::from_strs(vec!["Hello".to_owned(), "World".to_owned()]).unwrap();
+    assert_eq!(list.iter().collect::<Vec<_>>(), ["Hello", "World"]);
+    // Overflow is reported
+    assert!(stack_dst::Fifo::<str, ::stack_dst::buffers::Ptr2>::from_strs(&["a", "b"]).is_err());
+}
+
+#[test]
+fn is_empty() {
+    let mut list = DstFifo::<str>::new();
+    assert!(list.is_empty());
+    list.push_back_str("Hello").unwrap();
+    assert!(!list.is_empty());
+    list.pop_front();
+    assert!(list.is_empty());
+    assert_eq!(list.len(), 0);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn growable_vec() {
+    use stack_dst::buffers::{GrowableVec, Observed};
+    use std::cell::Cell;
+
+    // Count how often the fifo's buffer has to grow while pushing many small items
+    let n_grows = Cell::new(0);
+    let buf = Observed(GrowableVec::<u8>::new(), |_old, _new| {
+        n_grows.set(n_grows.get() + 1)
+    });
+    let mut list = stack_dst::Fifo::<str, _>::with_buffer(buf);
+    for i in 0..1000 {
+        list.push_back_str(&format!("{}", i)).unwrap();
+    }
+    assert_eq!(list.len(), 1000);
+    assert!(list.iter().map(|v| v.parse::<usize>().unwrap()).eq(0..1000));
+    // Doubling growth, so the count is logarithmic in the ~11KB pushed
+    assert!(n_grows.get() <= 15, "{} grows", n_grows.get());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn reserve_items() {
+    use stack_dst::buffers::{Observed, PtrVec};
+    use std::cell::Cell;
+    let n_grows = Cell::new(0);
+    let buf = Observed(PtrVec::new(), |_old, _new| n_grows.set(n_grows.get() + 1));
+    let mut list = stack_dst::Fifo::<dyn std::any::Any, _>::with_buffer(buf);
+    list.reserve_items::<u64>(100).unwrap();
+    assert_eq!(n_grows.get(), 1);
+    for i in 0..100u64 {
+        list.push_back_stable(i, |p| p).unwrap();
+    }
+    assert_eq!(list.len(), 100);
+    assert_eq!(n_grows.get(), 1);
+
+    // Overflowing the word count fails
+    assert!(list.reserve_items::<u64>(usize::MAX).is_err());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn reserve() {
+    let mut list = stack_dst::Fifo::<str, ::stack_dst::buffers::U8Vec>::new();
+    list.reserve_exact(20).unwrap();
+    assert_eq!(list.capacity(), 20);
+    list.push_back_str("Hello").unwrap();
+    assert_eq!(list.capacity(), 20);
+    list.reserve(100).unwrap();
+    assert!(list.capacity() >= 100);
+    // Existing items are kept
+    assert_eq!(list.front(), Some("Hello"));
+
+    // Fixed-size buffers can't grow
+    let mut list = DstFifo::<str>::new();
+    assert!(list.reserve_exact(8).is_ok());
+    assert!(list.reserve(9).is_err());
+    assert_eq!(list.capacity(), 8);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn reserve_overflow() {
+    // The required size overflows, so is rejected (for both growable and fixed buffers)
+    let mut list = stack_dst::Fifo::<str, ::stack_dst::buffers::U8Vec>::new();
+    list.push_back_str("Hello").unwrap();
+    assert!(list.reserve(usize::MAX).is_err());
+    assert!(list.reserve_exact(usize::MAX).is_err());
+    assert!(list.reserve_items::<u8>(usize::MAX).is_err());
+
+    let mut list = stack_dst::Fifo::<str, ::stack_dst::buffers::Ptr8>::new();
+    list.push_back_str("Hello").unwrap();
+    assert!(list.reserve(usize::MAX).is_err());
+    assert_eq!(list.len(), 1);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn observed_extends() {
+    use stack_dst::buffers::{GrowableVec, Observed};
+    use std::cell::Cell;
+    let n_grows = Cell::new(0);
+    let last_size = Cell::new(0);
+    let buf = Observed(GrowableVec::<usize>::new(), |old, new| {
+        assert_eq!(old, last_size.get());
+        assert!(new > old);
+        last_size.set(new);
+        n_grows.set(n_grows.get() + 1);
+    });
+    let mut list = stack_dst::Fifo::<[u32], _>::with_buffer(buf);
+    for i in 0..500 {
+        list.push_copied(&[i, i + 1]).unwrap();
+    }
+    assert_eq!(list.len(), 500);
+    assert_eq!(list.capacity(), last_size.get());
+    // Doubling growth, so only a few reallocations
+    assert!(
+        n_grows.get() > 0 && n_grows.get() <= 12,
+        "{}",
+        n_grows.get()
+    );
+}
+
+#[test]
+fn push_back_evicting() {
+    let mut queue = stack_dst::Fifo::<str, ::stack_dst::buffers::U8_32>::new();
+    // Each item takes 5 bytes of data plus the length
+    let n_fit = 32 / (5 + std::mem::size_of::<usize>());
+    for i in 0..n_fit {
+        assert_eq!(
+            queue.push_back_str_evicting(&format!("item{}", i)),
+            Ok(None)
+        );
+    }
+    for i in n_fit..10 {
+        assert_eq!(
+            queue.push_back_str_evicting(&format!("item{}", i)),
+            Ok(Some(()))
+        );
+        assert_eq!(queue.len(), n_fit);
+        assert_eq!(queue.front(), Some(&format!("item{}", i + 1 - n_fit)[..]));
+    }
+    assert_eq!(queue.iter().last(), Some("item9"));
+
+    // Items that can never fit are rejected without evicting anything
+    assert_eq!(queue.push_back_str_evicting(&"a".repeat(64)), Err(()));
+    assert_eq!(queue.len(), n_fit);
+}
+
+#[test]
+fn push_back_evicting_drops() {
+    use std::rc::Rc;
+
+    let counter = Rc::new(());
+    let mut queue = DstFifo::<dyn std::any::Any>::new();
+    let mut n_evicted = 0;
+    for _ in 0..10 {
+        if let Some(()) = queue
+            .push_back_evicting(counter.clone(), |v| v as _)
+            .unwrap()
+        {
+            n_evicted += 1;
+        }
+    }
+    assert!(n_evicted > 0);
+    // Only the items still in the queue hold references
+    assert_eq!(Rc::strong_count(&counter), 1 + queue.len());
+    drop(queue);
+    assert_eq!(Rc::strong_count(&counter), 1);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn clone_into_buffer() {
+    let mut queue = DstFifo::<str>::new();
+    queue.push_back_str("first").unwrap();
+    queue.push_back_str("second").unwrap();
+    queue.push_back_str("third").unwrap();
+    queue.pop_front();
+
+    let mut queue2 = queue
+        .clone_into_buffer::<::stack_dst::buffers::PtrVec>()
+        .unwrap();
+    assert_eq!(queue2.iter().collect::<Vec<_>>(), ["second", "third"]);
+    // The new queue can grow past the original's size
+    queue2.push_back_str(&"x".repeat(100)).unwrap();
+    assert_eq!(queue2.len(), 3);
+    assert_eq!(queue.len(), 2);
+
+    // And back again (fails if the items don't fit)
+    let queue3 = queue2.clone_into_buffer::<::stack_dst::buffers::Ptr8>();
+    assert!(queue3.is_err());
+    queue2.pop_front();
+    queue2.pop_front();
+    queue2.push_back_str("fourth").unwrap();
+    queue2.pop_front();
+    let queue3 = queue2
+        .clone_into_buffer::<::stack_dst::buffers::Ptr8>()
+        .unwrap();
+    assert_eq!(queue3.iter().collect::<Vec<_>>(), ["fourth"]);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn clone_compact() {
+    // A large (mostly unused) growable queue
+    let mut queue = ::stack_dst::Fifo::<str, ::stack_dst::buffers::PtrVec>::new();
+    queue.reserve(100).unwrap();
+    queue.push_back_str("dropped").unwrap();
+    queue.push_back_str("hi").unwrap();
+    queue.pop_front();
+
+    // One word for the data, one for the length
+    let small = queue.clone_compact::<::stack_dst::buffers::Ptr2>().unwrap();
+    assert_eq!(small.iter().collect::<Vec<_>>(), ["hi"]);
+    let exact = queue
+        .clone_compact::<::stack_dst::buffers::PtrVec>()
+        .unwrap();
+    assert_eq!(exact.capacity(), 2);
+    assert_eq!(exact.iter().collect::<Vec<_>>(), ["hi"]);
+
+    // Too large for the target buffer
+    queue.push_back_str("x").unwrap();
+    assert!(queue.clone_compact::<::stack_dst::buffers::Ptr2>().is_err());
+
+    let mut slices = ::stack_dst::Fifo::<[u16], ::stack_dst::buffers::PtrVec>::new();
+    slices.reserve(100).unwrap();
+    slices.push_copied(&[1, 2]).unwrap();
+    let small = slices
+        .clone_compact::<::stack_dst::buffers::Ptr2>()
+        .unwrap();
+    assert_eq!(small.front(), Some(&[1, 2][..]));
+}
+
+#[test]
+fn front_or_push() {
+    let mut queue = DstFifo::<[u32]>::new();
+    let mut calls = 0;
+    {
+        let v = queue
+            .front_or_push(
+                || {
+                    calls += 1;
+                    [1, 2]
+                },
+                |v| v,
+            )
+            .unwrap();
+        v[0] = 10;
+    }
+    let v = queue
+        .front_or_push(
+            || {
+                calls += 1;
+                [3, 4]
+            },
+            |v| v,
+        )
+        .unwrap();
+    assert_eq!(v, &[10, 2]);
+    assert_eq!(calls, 1);
+    assert_eq!(queue.len(), 1);
+
+    // Doesn't fit
+    let mut queue = stack_dst::Fifo::<[u8], ::stack_dst::buffers::Ptr2>::new();
+    assert_eq!(queue.front_or_push(|| [0u8; 64], |v| v), Err([0u8; 64]));
+}
+
+#[test]
+fn len_tracking() {
+    let mut queue = DstFifo::<[u8]>::new();
+    for i in 0..4u8 {
+        queue.push_copied(&[i]).unwrap();
+        assert_eq!(queue.len(), i as usize + 1);
+        assert_eq!(queue.iter().len(), queue.len());
+    }
+    queue.pop_front();
+    assert_eq!(queue.len(), 3);
+    queue.retain(|v| v[0] % 2 == 0);
+    assert_eq!(queue.len(), 1);
+    assert_eq!(queue.iter_mut().len(), 1);
+    queue.clear();
+    assert_eq!(queue.len(), 0);
+    assert!(queue.is_empty());
+    // The whole buffer is available again
+    assert_eq!(
+        queue.remaining_capacity_for::<[u8; 1]>(),
+        DstFifo::<[u8]>::new().remaining_capacity_for::<[u8; 1]>()
+    );
+    queue.push_copied(&[1]).unwrap();
+    assert_eq!(queue.len(), 1);
+}
+
+#[test]
+fn position_find() {
+    let queue = DstFifo::<str>::from_strs(["apple", "banana", "cherry", "bean"].iter()).unwrap();
+    assert_eq!(queue.position(|v| v.starts_with('b')), Some(1));
+    assert_eq!(queue.find(|v| v.starts_with('b')), Some("banana"));
+    assert_eq!(queue.position(|v| v.starts_with("ch")), Some(2));
+    assert_eq!(queue.position(|v| v.is_empty()), None);
+    assert_eq!(queue.find(|v| v.is_empty()), None);
+}
+
+#[test]
+#[cfg(all(debug_assertions, feature = "alloc"))]
+fn invariant_check() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let limit = std::rc::Rc::new(std::cell::Cell::new(usize::MAX));
+    let mut queue =
+        stack_dst::Fifo::<str, _>::with_buffer(ShrinkableBuf(Vec::new(), limit.clone()));
+    queue.push_back_str("Hello").unwrap();
+    assert_eq!(queue.len(), 1);
+
+    // Shrink the buffer out from under the fifo, then check that the next call notices
+    limit.set(0);
+    let err = catch_unwind(AssertUnwindSafe(|| queue.len())).expect_err("Corruption not detected");
+    let msg = err.downcast::<String>().unwrap();
+    assert!(msg.starts_with("Fifo invariant violated"), "{}", msg);
+
+    // Restore so the fifo can be dropped cleanly
+    limit.set(usize::MAX);
+}
+
+#[test]
+fn for_each() {
+    let mut queue = DstFifo::<dyn Fn() -> i32>::new();
+    queue.push_back_stable(|| 1, |p| p).ok().unwrap();
+    queue.push_back_stable(|| 2, |p| p).ok().unwrap();
+    queue.pop_front();
+    queue.push_back_stable(|| 3, |p| p).ok().unwrap();
+    let mut values = Vec::new();
+    queue.for_each(|f| values.push(f()));
+    assert_eq!(values, [2, 3]);
+
+    let mut queue = DstFifo::<[u8]>::new();
+    queue.push_copied(&[1, 2]).unwrap();
+    queue.push_copied(&[3]).unwrap();
+    queue.for_each_mut(|v| v[0] *= 10);
+    assert_eq!(queue.front(), Some(&[10, 2][..]));
+    queue.pop_front();
+    assert_eq!(queue.front(), Some(&[30][..]));
+}
+
+#[test]
+fn split_off_front() {
+    let mut queue = DstFifo::<str>::from_strs(["one", "two", "three", "four"].iter()).unwrap();
+    let front = queue.split_off_front(2).unwrap();
+    assert_eq!(front.len(), 2);
+    assert_eq!(front.iter().collect::<Vec<_>>(), ["one", "two"]);
+    assert_eq!(queue.len(), 2);
+    assert_eq!(queue.iter().collect::<Vec<_>>(), ["three", "four"]);
+
+    // More than the length moves everything
+    let mut rest = queue.split_off_front(10).unwrap();
+    assert!(queue.is_empty());
+    assert_eq!(rest.iter().collect::<Vec<_>>(), ["three", "four"]);
+    // Both are still usable
+    rest.push_back_str("five").unwrap();
+    queue.push_back_str("six").unwrap();
+    assert_eq!(rest.iter().collect::<Vec<_>>(), ["three", "four", "five"]);
+    assert_eq!(queue.iter().collect::<Vec<_>>(), ["six"]);
+}
+
+#[test]
+fn split_off_front_drops() {
+    use std::rc::Rc;
+    let v = Rc::new(());
+    let mut queue = DstFifo::<[Rc<()>]>::new();
+    queue.push_cloned(std::slice::from_ref(&v)).unwrap();
+    queue.push_cloned(&[v.clone(), v.clone()]).unwrap();
+    assert_eq!(Rc::strong_count(&v), 4);
+    let front = queue.split_off_front(1).unwrap();
+    assert_eq!(Rc::strong_count(&v), 4);
+    drop(front);
+    assert_eq!(Rc::strong_count(&v), 3);
+    drop(queue);
+    assert_eq!(Rc::strong_count(&v), 1);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn pop_poisons() {
+    use std::mem::MaybeUninit;
+    let mut buf = [MaybeUninit::new(0usize); 8];
+    {
+        let mut queue = stack_dst::Fifo::<[usize], _>::with_buffer(&mut buf);
+        queue.push_copied(&[1, 2]).unwrap();
+        queue.push_copied(&[3]).unwrap();
+        queue.pop_front();
+        queue.pop_front();
+        assert!(queue.is_empty());
+    }
+    let poison = usize::from_ne_bytes([0xDE; std::mem::size_of::<usize>()]);
+    for w in &buf[..5] {
+        assert_eq!(unsafe { w.assume_init() }, poison);
+    }
+    // Unused words are untouched
+    for w in &buf[5..] {
+        assert_eq!(unsafe { w.assume_init() }, 0);
+    }
+}
+
+#[test]
+fn remove_at() {
+    use std::rc::Rc;
+    let v = Rc::new(());
+    let mut queue = stack_dst::Fifo::<[Rc<()>], ::stack_dst::buffers::Ptr16>::new();
+    queue.push_cloned(&[v.clone(), v.clone()]).unwrap();
+    queue.push_cloned(std::slice::from_ref(&v)).unwrap();
+    queue
+        .push_cloned(&[v.clone(), v.clone(), v.clone()])
+        .unwrap();
+    assert_eq!(Rc::strong_count(&v), 7);
+
+    let offsets: Vec<usize> = queue.iter_with_offsets().map(|(o, _)| o).collect();
+    assert_eq!(offsets.len(), 3);
+    // Not the start of an item
+    assert!(!queue.remove_at(offsets[0] + 1));
+    assert!(!queue.remove_at(1000));
+    assert_eq!(queue.len(), 3);
+
+    // Remove the middle item
+    assert!(queue.remove_at(offsets[1]));
+    assert_eq!(Rc::strong_count(&v), 6);
+    assert_eq!(queue.len(), 2);
+    let lens: Vec<usize> = queue.iter().map(|v| v.len()).collect();
+    assert_eq!(lens, [2, 3]);
+
+    // Remove the front (offsets are re-collected, as removal invalidates them)
+    let ofs = queue.iter_with_offsets().next().unwrap().0;
+    assert!(queue.remove_at(ofs));
+    assert_eq!(Rc::strong_count(&v), 4);
+    assert_eq!(queue.front().map(|v| v.len()), Some(3));
+    // Space is reclaimed
+    queue.push_cloned(&[v.clone(), v.clone()]).unwrap();
+    drop(queue);
+    assert_eq!(Rc::strong_count(&v), 1);
+}
+
+#[test]
+fn remove_at_panic_safety() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    struct PanicOnDrop(bool);
+    impl Drop for PanicOnDrop {
+        fn drop(&mut self) {
+            if self.0 {
+                panic!("PanicOnDrop");
+            }
+        }
+    }
+    let mut queue = stack_dst::Fifo::<[PanicOnDrop], ::stack_dst::buffers::Ptr16>::new();
+    queue
+        .push_from_iter(Some(PanicOnDrop(false)).into_iter())
+        .unwrap();
+    queue
+        .push_from_iter(Some(PanicOnDrop(true)).into_iter())
+        .unwrap();
+    queue
+        .push_from_iter(Some(PanicOnDrop(false)).into_iter())
+        .unwrap();
+    let ofs = queue.iter_with_offsets().nth(1).unwrap().0;
+
+    assert!(catch_unwind(AssertUnwindSafe(|| queue.remove_at(ofs))).is_err());
+    // The items after the removed one are leaked, and no longer counted
+    assert_eq!(queue.len(), 1);
+    assert_eq!(queue.iter().len(), 1);
+    assert_eq!(queue.iter().count(), 1);
+}
+
+#[test]
+fn retain_panic_safety() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    let mut queue = DstFifo::<str>::new();
+    queue.push_back_str("a").unwrap();
+    queue.push_back_str("b").unwrap();
+    queue.push_back_str("c").unwrap();
+
+    let rv = catch_unwind(AssertUnwindSafe(|| {
+        queue.retain(|v| {
+            if v == "b" {
+                panic!("retain");
+            }
+            true
+        })
+    }));
+    assert!(rv.is_err());
+    // All items are leaked, and the count matches
+    assert!(queue.is_empty());
+    assert_eq!(queue.len(), 0);
+    assert_eq!(queue.pop_front_n(5), 0);
+    assert!(queue.into_single().is_err());
+}
+
+#[test]
+fn overhead_bytes() {
+    let word = std::mem::size_of::<usize>();
+    let mut queue = stack_dst::Fifo::<str, ::stack_dst::buffers::Ptr16>::new();
+    assert_eq!(queue.overhead_bytes(), 0);
+    assert_eq!(queue.capacity_bytes(), 16 * word);
+    for s in &["a", "bb", "ccc", "dddd"] {
+        queue.push_back_str(s).unwrap();
+    }
+    // One length word per item
+    assert_eq!(queue.overhead_bytes(), 4 * word);
+    queue.pop_front();
+    assert_eq!(queue.overhead_bytes(), 3 * word);
+
+    // Byte-granularity buffers still need a full length per item
+    let mut queue = stack_dst::Fifo::<str, ::stack_dst::buffers::U8_32>::new();
+    queue.push_back_str("x").unwrap();
+    queue.push_back_str("y").unwrap();
+    assert_eq!(queue.capacity_bytes(), 32);
+    assert_eq!(queue.overhead_bytes(), 2 * word);
+}
+
+#[test]
+fn pop_front_n_drops() {
+    use std::rc::Rc;
+    let v = Rc::new(());
+    let mut list = DstFifo::<[Rc<()>]>::new();
+    for _ in 0..4 {
+        list.push_cloned(std::slice::from_ref(&v)).unwrap();
+    }
+    assert_eq!(Rc::strong_count(&v), 5);
+    assert_eq!(list.pop_front_n(0), 0);
+    assert_eq!(list.pop_front_n(3), 3);
+    assert_eq!(Rc::strong_count(&v), 2);
+    assert_eq!(list.len(), 1);
+    assert!(list.front().is_some());
+    assert_eq!(list.pop_front_n(3), 1);
+    assert_eq!(Rc::strong_count(&v), 1);
+    assert!(list.is_empty());
+    assert_eq!(list.pop_front_n(1), 0);
+}
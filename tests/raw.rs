@@ -0,0 +1,26 @@
+extern crate stack_dst;
+
+use stack_dst::raw::{assemble_fat_ptr, split_fat_ptr};
+use std::fmt::Debug;
+
+#[test]
+fn round_trip_trait_object() {
+    let v = 1234u32;
+    let r: &dyn Debug = &v;
+    let (data, meta, len) = split_fat_ptr(r as *const dyn Debug).unwrap();
+    assert_eq!(data, &v as *const u32 as *const ());
+    assert_eq!(len, 1);
+    assert!(meta[len..].iter().all(|&w| w == 0));
+
+    let p: *const dyn Debug = unsafe { assemble_fat_ptr(data, &meta[..len]) };
+    assert_eq!(format!("{:?}", unsafe { &*p }), "1234");
+}
+
+#[test]
+fn round_trip_sized() {
+    let v = 1234u32;
+    let (data, _meta, len) = split_fat_ptr(&v as *const u32).unwrap();
+    assert_eq!(len, 0);
+    let p: *const u32 = unsafe { assemble_fat_ptr(data, &[]) };
+    assert_eq!(unsafe { *p }, 1234);
+}
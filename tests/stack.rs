@@ -2,6 +2,11 @@ use std::any::Any;
 
 extern crate stack_dst;
 
+mod common;
+use common::FailingAlloc;
+#[cfg(all(debug_assertions, feature = "alloc"))]
+use common::ShrinkableBuf;
+
 type DstStack<T> = stack_dst::Stack<T, ::stack_dst::buffers::Ptr8>;
 
 #[test]
@@ -182,36 +187,475 @@ fn slice_push_panic_safety_unaligned() {
 mod unaligned {
     use stack_dst::Stack;
     use std::any::Any;
-    type Buf8_16 = ::stack_dst::buffers::ArrayBuf<u8, ::stack_dst::buffers::n::U16>;
+    type BufU8 = ::stack_dst::buffers::U8_32;
     #[test]
     #[should_panic]
     fn push_stable() {
-        let mut stack = Stack::<dyn Any, Buf8_16>::new();
+        let mut stack = Stack::<dyn Any, BufU8>::new();
         let _ = stack.push_stable(123u32, |v| v as _);
     }
     #[test]
+    fn try_push_stable() {
+        let mut stack = Stack::<dyn Any, BufU8>::new();
+        let e = stack.try_push_stable(123u32, |v| v as _).unwrap_err();
+        assert_eq!(e.kind(), ::stack_dst::PushError::Misaligned);
+        assert_eq!(e.into_inner(), 123);
+        assert!(stack.is_empty());
+        // Values with suitable alignment can still be pushed
+        stack.try_push_stable(1u8, |v| v as _).unwrap();
+    }
+    #[test]
     #[should_panic]
     #[cfg(feature = "unsize")]
     fn push() {
-        let mut stack = Stack::<dyn Any, Buf8_16>::new();
+        let mut stack = Stack::<dyn Any, BufU8>::new();
         let _ = stack.push(123u32);
     }
     #[test]
     #[should_panic]
     fn push_cloned() {
-        let mut stack = Stack::<[u32], Buf8_16>::new();
+        let mut stack = Stack::<[u32], BufU8>::new();
         let _ = stack.push_cloned(&[123u32]);
     }
     #[test]
     #[should_panic]
     fn push_copied() {
-        let mut stack = Stack::<[u32], Buf8_16>::new();
+        let mut stack = Stack::<[u32], BufU8>::new();
         let _ = stack.push_copied(&[123u32]);
     }
     #[test]
     #[should_panic]
     fn push_from_iter() {
-        let mut stack = Stack::<[u32], Buf8_16>::new();
+        let mut stack = Stack::<[u32], BufU8>::new();
         let _ = stack.push_from_iter(0..1);
     }
 }
+
+#[test]
+fn push_errors() {
+    use stack_dst::PushError;
+    let mut stack = stack_dst::Stack::<dyn Any, ::stack_dst::buffers::Ptr2>::new();
+    assert_eq!(
+        stack
+            .try_push_stable([0usize; 2], |p| p)
+            .unwrap_err()
+            .kind(),
+        PushError::TooLarge
+    );
+    stack.try_push_stable(1usize, |p| p).unwrap();
+    assert_eq!(
+        stack.try_push_stable(2usize, |p| p).unwrap_err().kind(),
+        PushError::Full
+    );
+    // Once popped, the same push succeeds
+    stack.pop();
+    stack.try_push_stable(2usize, |p| p).unwrap();
+
+    let mut stack = stack_dst::Stack::<str, FailingAlloc>::new();
+    assert_eq!(stack.try_push_str("Hello"), Err(PushError::AllocFailed));
+}
+
+#[test]
+fn iter_len() {
+    let mut stack = DstStack::<dyn Any>::new();
+    stack.push_stable(1u8, |p| p).unwrap();
+    stack.push_stable(2u32, |p| p).unwrap();
+    stack.push_stable((), |p| p).unwrap();
+    assert_eq!(stack.len(), 3);
+    assert_eq!(stack.iter().len(), 3);
+    let mut it = stack.iter_mut();
+    assert_eq!(it.size_hint(), (3, Some(3)));
+    it.next();
+    assert_eq!(it.size_hint(), (2, Some(2)));
+    it.next();
+    it.next();
+    assert_eq!(it.size_hint(), (0, Some(0)));
+    assert!(it.next().is_none());
+    stack.pop();
+    assert_eq!(stack.iter().len(), 2);
+}
+
+#[test]
+fn retain() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static FLAGS: AtomicUsize = AtomicUsize::new(0);
+    struct Sentinel(usize);
+    impl ::std::ops::Drop for Sentinel {
+        fn drop(&mut self) {
+            let flag = 1 << self.0;
+            let v = FLAGS.fetch_or(1 << self.0, Ordering::SeqCst);
+            assert!(v & flag == 0);
+        }
+    }
+    impl AsRef<Sentinel> for Sentinel {
+        fn as_ref(&self) -> &Sentinel {
+            self
+        }
+    }
+    let mut stack: ::stack_dst::Stack<dyn AsRef<Sentinel>, ::stack_dst::buffers::Ptr16> =
+        ::stack_dst::Stack::new();
+    stack.push_stable(Sentinel(0), |v| v).ok().unwrap();
+    stack.push_stable(Sentinel(1), |v| v).ok().unwrap();
+    stack.push_stable(Sentinel(2), |v| v).ok().unwrap();
+    stack.push_stable(Sentinel(3), |v| v).ok().unwrap();
+    stack.push_stable(Sentinel(4), |v| v).ok().unwrap();
+
+    stack.retain(|v| v.as_ref().0 % 2 == 1);
+    assert_eq!(FLAGS.load(Ordering::SeqCst), 0b10_101);
+    assert_eq!(stack.len(), 2);
+    {
+        let mut it = stack.iter().map(|v| v.as_ref().0);
+        assert_eq!(it.next(), Some(3));
+        assert_eq!(it.next(), Some(1));
+        assert_eq!(it.next(), None);
+    }
+    // The stack should still be usable after compaction
+    stack.push_stable(Sentinel(5), |v| v).ok().unwrap();
+    assert_eq!(stack.top().map(|v| v.as_ref().0), Some(5));
+    drop(stack);
+    assert_eq!(FLAGS.load(Ordering::SeqCst), 0b111_111);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn clone_from_reuses_buffer() {
+    let mut src = stack_dst::Stack::<[String], stack_dst::buffers::PtrVec>::new();
+    src.push_cloned(&["a".to_owned(), "b".to_owned()]).unwrap();
+    src.push_cloned(&["c".to_owned()]).unwrap();
+
+    let mut dst = stack_dst::Stack::<[String], stack_dst::buffers::PtrVec>::new();
+    dst.push_cloned(&vec!["x".to_owned(); 8]).unwrap();
+    // `Clone` needs a `Default` buffer (which rules out `Observed`), but a `PtrVec` only changes
+    // capacity when it's extended
+    let capacity = dst.capacity();
+    dst.clone_from(&src);
+    assert_eq!(dst.capacity(), capacity);
+    assert_eq!(dst.len(), 2);
+    let mut it = dst.iter();
+    assert_eq!(it.next(), Some(&["c".to_owned()][..]));
+    assert_eq!(it.next(), Some(&["a".to_owned(), "b".to_owned()][..]));
+    assert_eq!(it.next(), None);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn reserve_items() {
+    use stack_dst::buffers::{Observed, PtrVec};
+    use std::cell::Cell;
+    let n_grows = Cell::new(0);
+    let buf = Observed(PtrVec::new(), |_old, _new| n_grows.set(n_grows.get() + 1));
+    let mut stack = stack_dst::Stack::<dyn Any, _>::with_buffer(buf);
+    stack.reserve_items::<u64>(100).unwrap();
+    assert_eq!(n_grows.get(), 1);
+    for i in 0..100u64 {
+        stack.push_stable(i, |p| p).unwrap();
+    }
+    assert_eq!(stack.len(), 100);
+    assert_eq!(n_grows.get(), 1);
+
+    // Overflowing the word count fails
+    assert!(stack.reserve_items::<u64>(usize::MAX).is_err());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn reserve() {
+    let mut stack = stack_dst::Stack::<str, ::stack_dst::buffers::U8Vec>::new();
+    stack.reserve_exact(20).unwrap();
+    assert_eq!(stack.capacity(), 20);
+    stack.push_str("Hello").unwrap();
+    assert_eq!(stack.capacity(), 20);
+    stack.reserve(100).unwrap();
+    assert!(stack.capacity() >= 100);
+    // Existing items are kept
+    assert_eq!(stack.top(), Some("Hello"));
+
+    // Fixed-size buffers can't grow
+    let mut stack = DstStack::<str>::new();
+    assert!(stack.reserve_exact(8).is_ok());
+    assert!(stack.reserve(9).is_err());
+    assert_eq!(stack.capacity(), 8);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn reserve_overflow() {
+    // The required size overflows, so is rejected (for both growable and fixed buffers)
+    let mut stack = stack_dst::Stack::<str, ::stack_dst::buffers::U8Vec>::new();
+    stack.push_str("Hello").unwrap();
+    assert!(stack.reserve(usize::MAX).is_err());
+    assert!(stack.reserve_exact(usize::MAX).is_err());
+    assert!(stack.reserve_items::<u8>(usize::MAX).is_err());
+
+    let mut stack = stack_dst::Stack::<str, ::stack_dst::buffers::Ptr8>::new();
+    stack.push_str("Hello").unwrap();
+    assert!(stack.reserve(usize::MAX).is_err());
+    assert_eq!(stack.len(), 1);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn collect_cloned() {
+    let mut stack = DstStack::<str>::new();
+    stack.push_str("bottom").unwrap();
+    stack.push_str("middle").unwrap();
+    stack.push_str("top").unwrap();
+    assert_eq!(stack.collect_cloned(), ["top", "middle", "bottom"]);
+    stack.pop();
+    assert_eq!(stack.collect_cloned(), ["middle", "bottom"]);
+
+    let mut stack = stack_dst::Stack::<[String], ::stack_dst::buffers::Ptr16>::new();
+    stack.push_cloned(&["a".to_owned()]).unwrap();
+    stack
+        .push_cloned(&["b".to_owned(), "c".to_owned()])
+        .unwrap();
+    assert_eq!(
+        stack.collect_cloned(),
+        [vec!["b".to_owned(), "c".to_owned()], vec!["a".to_owned()]]
+    );
+}
+
+#[test]
+fn remaining_capacity_for() {
+    use std::any::Any;
+
+    let mut stack = stack_dst::Stack::<dyn Any, ::stack_dst::buffers::U64_8>::new();
+    // Each item needs a word for the value plus the vtable pointer
+    let per_item = 1 + (std::mem::size_of::<usize>() + 7) / 8;
+    let n = stack.remaining_capacity_for::<u64>();
+    assert_eq!(n, 8 / per_item);
+    for i in 0..n {
+        assert_eq!(stack.remaining_capacity_for::<u64>(), n - i);
+        stack.push_stable(i as u64, |v| v as _).unwrap();
+    }
+    assert_eq!(stack.remaining_capacity_for::<u64>(), 0);
+    assert!(stack.push_stable(0u64, |v| v as _).is_err());
+}
+
+#[test]
+fn top_or_push() {
+    let mut stack = DstStack::<[u32]>::new();
+    let mut calls = 0;
+    stack
+        .top_or_push(
+            || {
+                calls += 1;
+                [1, 2]
+            },
+            |v| v,
+        )
+        .unwrap()[0] = 10;
+    let v = stack
+        .top_or_push(
+            || {
+                calls += 1;
+                [3, 4]
+            },
+            |v| v,
+        )
+        .unwrap();
+    assert_eq!(v, &[10, 2]);
+    assert_eq!(calls, 1);
+    assert_eq!(stack.len(), 1);
+}
+
+#[test]
+fn len_tracking() {
+    let mut stack = DstStack::<[u8]>::new();
+    for i in 0..4u8 {
+        stack.push_copied(&[i]).unwrap();
+        assert_eq!(stack.len(), i as usize + 1);
+        assert_eq!(stack.iter().len(), stack.len());
+    }
+    stack.pop();
+    assert_eq!(stack.len(), 3);
+    stack.retain(|v| v[0] % 2 == 0);
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack.iter_mut().len(), 2);
+    stack.clear();
+    assert_eq!(stack.len(), 0);
+    assert!(stack.is_empty());
+    stack.push_copied(&[1]).unwrap();
+    assert_eq!(stack.len(), 1);
+}
+
+#[test]
+#[cfg(all(debug_assertions, feature = "alloc"))]
+fn invariant_check() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let limit = std::rc::Rc::new(std::cell::Cell::new(usize::MAX));
+    let mut stack =
+        stack_dst::Stack::<str, _>::with_buffer(ShrinkableBuf(Vec::new(), limit.clone()));
+    stack.push_str("Hello").unwrap();
+    assert_eq!(stack.len(), 1);
+
+    // Shrink the buffer out from under the stack, then check that the next call notices
+    limit.set(0);
+    let err = catch_unwind(AssertUnwindSafe(|| stack.len())).expect_err("Corruption not detected");
+    let msg = err.downcast::<String>().unwrap();
+    assert!(msg.starts_with("Stack invariant violated"), "{}", msg);
+
+    // Restore so the stack can be dropped cleanly
+    limit.set(usize::MAX);
+}
+
+#[test]
+fn for_each() {
+    let mut stack = DstStack::<dyn Fn() -> i32>::new();
+    let base = 10;
+    stack.push_stable(|| 1, |p| p).ok().unwrap();
+    stack.push_stable(move || base + 2, |p| p).ok().unwrap();
+    stack.push_stable(|| 3, |p| p).ok().unwrap();
+    let mut total = 0;
+    stack.for_each(|f| total += f());
+    assert_eq!(total, 16);
+    let mut values = Vec::new();
+    stack.for_each(|f| values.push(f()));
+    assert_eq!(values, [3, 12, 1]);
+
+    let mut stack = DstStack::<[u8]>::new();
+    stack.push_copied(&[1, 2]).unwrap();
+    stack.push_copied(&[3]).unwrap();
+    stack.for_each_mut(|v| v[0] *= 10);
+    assert_eq!(stack.iter().collect::<Vec<_>>(), [&[30][..], &[10, 2][..]]);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn pop_poisons() {
+    use std::mem::MaybeUninit;
+    let mut buf = [MaybeUninit::new(0usize); 8];
+    {
+        let mut stack = stack_dst::Stack::<[usize], _>::with_buffer(&mut buf);
+        stack.push_copied(&[1, 2]).unwrap();
+        stack.push_copied(&[3]).unwrap();
+        stack.pop();
+        assert_eq!(stack.top(), Some(&[1, 2][..]));
+    }
+    let poison = usize::from_ne_bytes([0xDE; std::mem::size_of::<usize>()]);
+    // All items have now been popped (the first by `pop`, the second on drop)
+    for w in &buf[8 - 5..] {
+        assert_eq!(unsafe { w.assume_init() }, poison);
+    }
+}
+
+#[test]
+fn fixed_stack_index() {
+    trait Shape {
+        fn area(&self) -> u32;
+        fn double(&mut self);
+    }
+    struct Rect(u32, u32);
+    impl Shape for Rect {
+        fn area(&self) -> u32 {
+            self.0 * self.1
+        }
+        fn double(&mut self) {
+            self.0 *= 2;
+        }
+    }
+    struct Square(u64);
+    impl Shape for Square {
+        fn area(&self) -> u32 {
+            (self.0 * self.0) as u32
+        }
+        fn double(&mut self) {
+            self.0 *= 2;
+        }
+    }
+
+    let mut stack = stack_dst::FixedStack::<dyn Shape, ::stack_dst::buffers::Ptr16>::new();
+    assert_eq!(stack.item_size(), None);
+    for i in 0..4 {
+        stack.push_stable(Rect(i, 2), |p| p).ok().unwrap();
+        stack.push_stable(Square(u64::from(i)), |p| p).ok().unwrap();
+    }
+    assert_eq!(stack.len(), 8);
+    assert_eq!(stack.item_size(), Some(8));
+    // Indexed top first, matching `iter`
+    for (i, v) in stack.iter().enumerate() {
+        assert_eq!(stack.get(i).unwrap().area(), v.area());
+    }
+    assert_eq!(stack.get(0).unwrap().area(), 9);
+    assert_eq!(stack.get(7).unwrap().area(), 0);
+    assert_eq!(stack.get(6).unwrap().area(), 0);
+    assert_eq!(stack.get(5).unwrap().area(), 2);
+    assert!(stack.get(8).is_none());
+
+    stack.get_mut(5).unwrap().double();
+    assert_eq!(stack.get(5).unwrap().area(), 4);
+}
+
+#[test]
+fn fixed_stack_size_mismatch() {
+    use stack_dst::PushError;
+    let mut stack = stack_dst::FixedStack::<dyn Any, ::stack_dst::buffers::Ptr8>::new();
+    stack.push_stable(1u32, |p| p).unwrap();
+    let e = stack.try_push_stable(2u16, |p| p).unwrap_err();
+    assert_eq!(e.kind(), PushError::SizeMismatch);
+    assert_eq!(e.into_inner(), 2u16);
+    assert_eq!(stack.push_stable(3u64, |p| p), Err(3u64));
+    assert_eq!(stack.len(), 1);
+
+    // Emptying the stack releases the size
+    stack.pop();
+    stack.push_stable(4u64, |p| p).unwrap();
+    assert_eq!(stack.item_size(), Some(8));
+    assert_eq!(stack.get(0).unwrap().downcast_ref::<u64>(), Some(&4));
+}
+
+#[test]
+fn into_single() {
+    let stack = DstStack::<str>::new();
+    let mut stack = stack.into_single().err().unwrap();
+    stack.push_str("Hello").unwrap();
+    stack.push_str("world").unwrap();
+    let mut stack = stack.into_single().err().unwrap();
+    assert_eq!(stack.len(), 2);
+    stack.pop();
+    let v: stack_dst::Value<str, _> = stack.into_single().ok().unwrap();
+    assert_eq!(&v[..], "Hello");
+    assert_eq!(v.len(), 5);
+
+    // The item is moved (not dropped) by the conversion
+    use std::cell::Cell;
+    use std::rc::Rc;
+    struct Tracked(Rc<Cell<usize>>);
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+    let drops = Rc::new(Cell::new(0));
+    let mut stack = DstStack::<dyn Any>::new();
+    stack
+        .push_stable(Tracked(drops.clone()), |p| p)
+        .ok()
+        .unwrap();
+    let v = stack.into_single().ok().unwrap();
+    assert_eq!(drops.get(), 0);
+    assert!(v.downcast_ref::<Tracked>().is_some());
+    drop(v);
+    assert_eq!(drops.get(), 1);
+}
+
+#[test]
+fn pop_n_drops() {
+    use std::rc::Rc;
+    let v = Rc::new(());
+    let mut list = DstStack::<[Rc<()>]>::new();
+    for _ in 0..4 {
+        list.push_cloned(std::slice::from_ref(&v)).unwrap();
+    }
+    assert_eq!(Rc::strong_count(&v), 5);
+    assert_eq!(list.pop_n(0), 0);
+    assert_eq!(list.pop_n(3), 3);
+    assert_eq!(Rc::strong_count(&v), 2);
+    assert_eq!(list.len(), 1);
+    assert!(list.top().is_some());
+    assert_eq!(list.pop_n(3), 1);
+    assert_eq!(Rc::strong_count(&v), 1);
+    assert!(list.is_empty());
+    assert_eq!(list.pop_n(1), 0);
+}
@@ -0,0 +1,76 @@
+#![cfg(feature = "std")]
+extern crate stack_dst;
+
+use stack_dst::sync::SyncFifo;
+use std::sync::Arc;
+
+#[test]
+fn threads() {
+    const THREADS: usize = 4;
+    const ITEMS: usize = 100;
+    let queue = Arc::new(SyncFifo::<str, ::stack_dst::buffers::PtrVec>::new());
+    let producers: Vec<_> = (0..THREADS)
+        .map(|t| {
+            let queue = queue.clone();
+            ::std::thread::spawn(move || {
+                for i in 0..ITEMS {
+                    queue.push_back_str(&format!("{}:{}", t, i)).unwrap();
+                }
+            })
+        })
+        .collect();
+    let consumer = {
+        let queue = queue.clone();
+        ::std::thread::spawn(move || {
+            let mut last_seen = [None; THREADS];
+            let mut count = 0;
+            while count < THREADS * ITEMS {
+                if let Some(v) = queue.pop_front() {
+                    let mut it = v.split(':').map(|v| v.parse::<usize>().unwrap());
+                    let (t, i) = (it.next().unwrap(), it.next().unwrap());
+                    // Items from each producer arrive in order
+                    assert!(last_seen[t].map_or(true, |l| l < i));
+                    last_seen[t] = Some(i);
+                    count += 1;
+                } else {
+                    ::std::thread::yield_now();
+                }
+            }
+        })
+    };
+    for p in producers {
+        p.join().unwrap();
+    }
+    consumer.join().unwrap();
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn slices() {
+    let queue = SyncFifo::<[u32], ::stack_dst::buffers::Ptr8>::new();
+    queue.push_cloned(&[1, 2, 3]).unwrap();
+    queue.push_cloned(&[]).unwrap();
+    assert_eq!(queue.len(), 2);
+    assert_eq!(queue.pop_front(), Some(vec![1, 2, 3]));
+    assert_eq!(queue.pop_front(), Some(vec![]));
+    assert_eq!(queue.pop_front(), None);
+}
+
+#[test]
+fn poisoned() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    let queue = Arc::new(SyncFifo::<str, ::stack_dst::buffers::Ptr8>::new());
+    queue.push_back_str("Hello").unwrap();
+    let res = {
+        let queue = queue.clone();
+        ::std::thread::spawn(move || {
+            let _lock = queue.lock();
+            panic!("Panic while locked");
+        })
+        .join()
+    };
+    assert!(res.is_err());
+    // The queue may be in an inconsistent state, so all access panics
+    assert!(catch_unwind(AssertUnwindSafe(|| queue.len())).is_err());
+    assert!(catch_unwind(AssertUnwindSafe(|| queue.push_back_str("World"))).is_err());
+}
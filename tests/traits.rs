@@ -0,0 +1,48 @@
+extern crate stack_dst;
+
+use stack_dst::traits::Deque;
+
+/// Sum all items, popping them as they're visited
+fn sum_all<Q: Deque<Item = [u32]>>(q: &mut Q) -> Vec<u32> {
+    let mut rv = Vec::new();
+    while let Some(v) = q.peek_mut() {
+        v[0] += 1;
+        rv.push(v.iter().sum());
+        q.pop();
+    }
+    rv
+}
+
+#[test]
+fn generic_over_both() {
+    let mut stack = stack_dst::Stack::<[u32], ::stack_dst::buffers::Ptr8>::new();
+    stack.push_copied(&[1, 2]).unwrap();
+    stack.push_copied(&[10]).unwrap();
+    assert_eq!(Deque::len(&stack), 2);
+    assert_eq!(sum_all(&mut stack), [11, 4]);
+    assert!(Deque::is_empty(&stack));
+
+    let mut fifo = stack_dst::Fifo::<[u32], ::stack_dst::buffers::Ptr8>::new();
+    fifo.push_copied(&[1, 2]).unwrap();
+    fifo.push_copied(&[10]).unwrap();
+    assert_eq!(Deque::len(&fifo), 2);
+    assert_eq!(sum_all(&mut fifo), [4, 11]);
+    assert!(Deque::is_empty(&fifo));
+}
+
+#[test]
+fn value_view_owned_and_borrowed() {
+    use stack_dst::traits::ValueView;
+    use stack_dst::{Value, ValueRef};
+    fn describe<V: ValueView<Item = str>>(v: &V) -> String {
+        format!("{} ({} bytes)", v.view(), v.size_of_val())
+    }
+    let owned = Value::<str, ::stack_dst::buffers::Ptr8>::new_str("Hello")
+        .ok()
+        .unwrap();
+    let text = String::from("Borrowed");
+    let borrowed = ValueRef::new(&text[..]);
+    assert_eq!(describe(&owned), "Hello (5 bytes)");
+    assert_eq!(describe(&borrowed), "Borrowed (8 bytes)");
+    assert_eq!(borrowed.into_inner().as_ptr(), text.as_ptr());
+}
@@ -1,3 +1,5 @@
+// `ensure_drop` compares against `bool` literals
+#![allow(clippy::bool_comparison)]
 extern crate stack_dst;
 
 type Value2w<T /*: ?Sized*/> = stack_dst::Value<T, ::stack_dst::buffers::Ptr2>;
@@ -112,12 +114,23 @@ mod unaligned {
     use stack_dst::Value;
     use std::any::Any;
 
-    type BufU8_16 = ::stack_dst::buffers::ArrayBuf<u8, ::stack_dst::buffers::n::U16>;
+    type BufU8 = ::stack_dst::buffers::U8_32;
 
     #[test]
     #[should_panic]
     fn new_stable() {
-        let _ = Value::<dyn Any, BufU8_16>::new_stable(1234u32, |v| v);
+        let _ = Value::<dyn Any, BufU8>::new_stable(1234u32, |v| v);
+    }
+    #[test]
+    fn try_new_stable() {
+        let e = Value::<dyn Any, BufU8>::try_new_stable(1234u32, |v| v).unwrap_err();
+        assert_eq!(e.kind(), ::stack_dst::PushError::Misaligned);
+        assert_eq!(e.into_inner(), 1234);
+        // An over-aligned buffer can hold the value
+        let v =
+            Value::<dyn Any, ::stack_dst::buffers::Align16_U8_32>::try_new_stable(1234u32, |v| v)
+                .unwrap();
+        assert_eq!(v.downcast_ref::<u32>(), Some(&1234));
     }
     #[test]
     #[should_panic]
@@ -132,7 +145,7 @@ mod unaligned {
     #[should_panic]
     #[cfg(feature = "unsize")]
     fn new() {
-        let _ = Value::<dyn Any, BufU8_16>::new(1234u32);
+        let _ = Value::<dyn Any, BufU8>::new(1234u32);
     }
     #[test]
     #[should_panic]
@@ -144,12 +157,12 @@ mod unaligned {
     #[should_panic]
     #[cfg(all(feature = "unsize", feature = "alloc"))]
     fn new_or_boxed() {
-        let _ = Value::<dyn Any, BufU8_16>::new_or_boxed(1234u32);
+        let _ = Value::<dyn Any, BufU8>::new_or_boxed(1234u32);
     }
     #[test]
     #[should_panic]
     fn empty_slice() {
-        let _ = Value::<[u32], BufU8_16>::empty_slice();
+        let _ = Value::<[u32], BufU8>::empty_slice();
     }
     #[test]
     #[should_panic]
@@ -157,3 +170,1604 @@ mod unaligned {
         let _ = Value::<[u32], _>::empty_slice_with_buffer([::std::mem::MaybeUninit::new(0u8); 16]);
     }
 }
+
+#[test]
+fn signed_buffer() {
+    use std::fmt::Display;
+    #[cfg(feature = "typenum_buffers")]
+    type BufI64_4 = ::stack_dst::buffers::ArrayBuf<i64, ::stack_dst::buffers::n::U4>;
+    #[cfg(not(feature = "typenum_buffers"))]
+    type BufI64_4 = ::stack_dst::buffers::ConstArrayBuf<i64, 4>;
+    let val = stack_dst::Value::<dyn Display, BufI64_4>::new_stable(1234u64, |v| v as _).unwrap();
+    assert_eq!(format!("{}", val), "1234");
+}
+
+#[test]
+fn take_str() {
+    let mut s = Value8w::<str>::new_str("Hello").unwrap();
+    let t = s.take().unwrap();
+    assert_eq!(&s[..], "");
+    assert_eq!(&t[..], "Hello");
+    // The emptied value is still usable
+    s.append_str("World").unwrap();
+    assert_eq!(&s[..], "World");
+}
+
+#[test]
+fn take_slice() {
+    let mut v = Value8w::<[String]>::empty_slice().unwrap();
+    v.append("a".to_owned()).unwrap();
+    v.append("b".to_owned()).unwrap();
+    let w = v.take().unwrap();
+    assert!(v.is_empty());
+    assert_eq!(&w[..], &["a".to_owned(), "b".to_owned()]);
+}
+
+#[test]
+fn sort_slice() {
+    let mut v = Value8w::<[i32]>::new_stable([5, -1, 3, 3, 0], |p| p).unwrap();
+    v.sort_unstable();
+    assert_eq!(&v[..], &[-1, 0, 3, 3, 5]);
+    v.sort_unstable_by(|a, b| b.cmp(a));
+    assert_eq!(&v[..], &[5, 3, 3, 0, -1]);
+    #[cfg(feature = "alloc")]
+    {
+        v.sort();
+        assert_eq!(&v[..], &[-1, 0, 3, 3, 5]);
+        v.sort_by(|a, b| b.cmp(a));
+        assert_eq!(&v[..], &[5, 3, 3, 0, -1]);
+    }
+}
+
+#[test]
+fn over_aligned_buffer() {
+    use std::any::Any;
+    #[derive(Debug, PartialEq)]
+    #[repr(align(16))]
+    struct Aligned(u8);
+    let val = stack_dst::Value::<dyn Any, ::stack_dst::buffers::Align16_U8_64>::new_stable(
+        Aligned(12),
+        |p| p,
+    )
+    .unwrap();
+    assert_eq!(&*val as *const dyn Any as *const u8 as usize % 16, 0);
+    assert_eq!(val.downcast_ref::<Aligned>(), Some(&Aligned(12)));
+    let val =
+        stack_dst::Value::<dyn Any, ::stack_dst::buffers::Align16_U8_64>::new_stable(-5i128, |p| p)
+            .unwrap();
+    assert_eq!(val.downcast_ref::<i128>(), Some(&-5));
+}
+
+#[test]
+#[should_panic]
+#[cfg(feature = "alloc")]
+fn over_aligned_buffer_vec() {
+    // The wrapper doesn't help when the data is elsewhere
+    type Buf = ::stack_dst::buffers::A16<::stack_dst::buffers::U8Vec>;
+    #[repr(align(16))]
+    struct Aligned;
+    let _ = stack_dst::Value::<dyn std::any::Any, Buf>::new_stable(Aligned, |p| p);
+}
+
+#[test]
+fn str_split_off() {
+    let mut s = Value8w::<str>::new_str("HelloWorld").unwrap();
+    let t = s.split_off(5).unwrap();
+    assert_eq!(&s[..], "Hello");
+    assert_eq!(&t[..], "World");
+
+    // Splitting at the end gives an empty tail
+    let t = s.split_off(5).unwrap();
+    assert_eq!(&s[..], "Hello");
+    assert_eq!(&t[..], "");
+}
+
+#[test]
+#[should_panic]
+fn str_split_off_boundary() {
+    let mut s = Value8w::<str>::new_str("Héllo").unwrap();
+    let _ = s.split_off(2);
+}
+
+#[test]
+fn slice_split_off() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    #[derive(Debug, PartialEq)]
+    struct Tracked(String, Rc<Cell<usize>>);
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.1.set(self.1.get() + 1);
+        }
+    }
+    let drops = Rc::new(Cell::new(0));
+    #[cfg(feature = "typenum_buffers")]
+    type Buf = ::stack_dst::buffers::ArrayBuf<usize, ::stack_dst::buffers::n::U32>;
+    #[cfg(not(feature = "typenum_buffers"))]
+    type Buf = ::stack_dst::buffers::ConstArrayBuf<usize, 32>;
+    let mut v = stack_dst::Value::<[Tracked], Buf>::empty_slice().unwrap();
+    for s in &["a", "b", "c", "d"] {
+        v.append(Tracked(s.to_string(), drops.clone())).unwrap();
+    }
+    let w = v.split_off(1).unwrap();
+    assert_eq!(drops.get(), 0);
+    assert_eq!(v.iter().map(|v| &v.0[..]).collect::<Vec<_>>(), ["a"]);
+    assert_eq!(
+        w.iter().map(|v| &v.0[..]).collect::<Vec<_>>(),
+        ["b", "c", "d"]
+    );
+    drop(v);
+    assert_eq!(drops.get(), 1);
+    drop(w);
+    assert_eq!(drops.get(), 4);
+}
+
+#[test]
+fn sized() {
+    let val = Value2w::<u64>::new_sized(0x1234_5678_9ABC).unwrap();
+    assert_eq!(*val, 0x1234_5678_9ABC);
+    // No metadata, so the value can use the entire buffer
+    let val = Value2w::<[usize; 2]>::new_sized([1, 2]).unwrap();
+    assert_eq!(*val, [1, 2]);
+    assert!(Value2w::<[usize; 3]>::new_sized([1, 2, 3]).is_err());
+}
+
+#[test]
+// A user-defined DST (sized struct with a trailing slice)
+fn custom_dst() {
+    #[repr(C)]
+    struct Packet<T: ?Sized> {
+        kind: u16,
+        data: T,
+    }
+    let v = stack_dst::Value::<Packet<[u8]>, ::stack_dst::buffers::Ptr8>::new_stable(
+        Packet {
+            kind: 0x1234,
+            data: [1u8, 2, 3, 4, 5],
+        },
+        |p| p as _,
+    )
+    .ok()
+    .unwrap();
+    assert_eq!(v.kind, 0x1234);
+    assert_eq!(&v.data, &[1, 2, 3, 4, 5]);
+}
+
+#[test]
+#[cfg(feature = "ptr_metadata")]
+// A user-defined DST with trait object metadata (`DynMetadata`, rather than a `usize` length)
+fn custom_dst_dyn_metadata() {
+    use std::fmt::Display;
+    #[repr(C)]
+    struct Tagged<T: ?Sized> {
+        tag: u8,
+        inner: T,
+    }
+    let v = stack_dst::Value::<Tagged<dyn Display>, ::stack_dst::buffers::Ptr8>::new_stable(
+        Tagged {
+            tag: 7,
+            inner: 1234u32,
+        },
+        |p| p as _,
+    )
+    .ok()
+    .unwrap();
+    assert_eq!(v.tag, 7);
+    assert_eq!(v.inner.to_string(), "1234");
+}
+
+#[test]
+fn slice_from_fn() {
+    let v = Value8w::<[u32]>::from_fn(7, |i| (i * i) as u32).unwrap();
+    assert_eq!(&v[..], &[0, 1, 4, 9, 16, 25, 36]);
+    assert!(Value8w::<[usize]>::from_fn(8, |i| i).is_err());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn slice_from_fn_overflow() {
+    // The byte size overflows, so must be rejected before anything is written
+    assert!(Value8w::<[u32]>::from_fn(usize::MAX / 2, |_| unreachable!()).is_err());
+    assert!(Value8w::<[u8]>::from_fn(usize::MAX, |_| unreachable!()).is_err());
+    assert!(
+        ::stack_dst::Value::<[u32], ::stack_dst::buffers::PtrVec>::from_fn(
+            usize::MAX / 2,
+            |_| unreachable!()
+        )
+        .is_err()
+    );
+}
+
+#[test]
+fn slice_from_fn_panic_safety() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    struct Sentinel;
+    impl Drop for Sentinel {
+        fn drop(&mut self) {
+            COUNT.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let rv = ::std::panic::catch_unwind(|| {
+        Value8w::<[Sentinel]>::from_fn(5, |i| {
+            if i == 3 {
+                panic!();
+            }
+            Sentinel
+        })
+    });
+    assert!(rv.is_err());
+    // All items created before the panic are dropped (exactly once)
+    assert_eq!(COUNT.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn slice_fill() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    #[derive(Debug)]
+    struct Tracked(String, Rc<Cell<usize>>);
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.1.set(self.1.get() + 1);
+        }
+    }
+    let drops = Rc::new(Cell::new(0));
+    #[cfg(feature = "typenum_buffers")]
+    type Buf = ::stack_dst::buffers::ArrayBuf<usize, ::stack_dst::buffers::n::U32>;
+    #[cfg(not(feature = "typenum_buffers"))]
+    type Buf = ::stack_dst::buffers::ConstArrayBuf<usize, 32>;
+    let mut v = stack_dst::Value::<[Tracked], Buf>::empty_slice().unwrap();
+    for s in &["a", "b", "c"] {
+        v.append(Tracked(s.to_string(), drops.clone())).unwrap();
+    }
+    let mut n = 0;
+    v.fill_with(|| {
+        n += 1;
+        Tracked(format!("x{}", n), drops.clone())
+    });
+    assert_eq!(drops.get(), 3);
+    assert_eq!(
+        v.iter().map(|v| &v.0[..]).collect::<Vec<_>>(),
+        ["x1", "x2", "x3"]
+    );
+
+    let mut v = Value8w::<[String]>::empty_slice().unwrap();
+    v.append("a".to_owned()).unwrap();
+    v.append("b".to_owned()).unwrap();
+    v.fill("z".to_owned());
+    assert_eq!(&v[..], &["z".to_owned(), "z".to_owned()]);
+}
+
+#[test]
+fn transmute_contents() {
+    use std::any::Any;
+    let mut val = Value2w::<dyn Any>::new_stable((0x1234_5678u32, 0x9ABC_DEF0u32), |p| p).unwrap();
+    unsafe {
+        val.transmute_contents(
+            |(a, b): (u32, u32)| [a as u16, (a >> 16) as u16, b as u16, (b >> 16) as u16],
+            |p: &[u16; 4]| p,
+        );
+    }
+    assert!(val.downcast_ref::<(u32, u32)>().is_none());
+    assert_eq!(
+        val.downcast_ref::<[u16; 4]>(),
+        Some(&[0x5678, 0x1234, 0xDEF0, 0x9ABC])
+    );
+}
+
+#[test]
+#[should_panic(expected = "transmute_contents: size_of::<U>() (4) != size_of::<U2>() (8)")]
+fn transmute_contents_size_mismatch() {
+    use std::any::Any;
+    let mut val = Value2w::<dyn Any>::new_stable(1u32, |p| p).unwrap();
+    unsafe {
+        val.transmute_contents(|v: u32| v as u64, |p: &u64| p);
+    }
+}
+
+#[test]
+fn cache_line_buffer() {
+    use stack_dst::buffers::{CacheLine, Ptr8};
+    assert_eq!(::std::mem::align_of::<CacheLine<Ptr8>>(), 64);
+    let vals: Vec<stack_dst::Value<dyn Fn() -> usize, CacheLine<Ptr8>>> = (0..4usize)
+        .map(|i| {
+            stack_dst::Value::new_stable(move || i, |p| p as _)
+                .ok()
+                .unwrap()
+        })
+        .collect();
+    for (i, v) in vals.iter().enumerate() {
+        assert_eq!(v(), i);
+        assert_eq!(&**v as *const _ as *const u8 as usize % 64, 0);
+    }
+}
+
+#[test]
+fn default_empty() {
+    let s = Value8w::<str>::default();
+    assert_eq!(&s[..], "");
+    let v = Value8w::<[String]>::default();
+    assert!(v.is_empty());
+
+    #[derive(Default)]
+    struct Holder {
+        name: Value8w<str>,
+        items: Value8w<[u32]>,
+    }
+    let h = Holder::default();
+    assert_eq!(&h.name[..], "");
+    assert_eq!(&h.items[..], &[]);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn leak() {
+    let v = 1234;
+    let f: &'static mut dyn Fn() -> i32 =
+        Value8w::<dyn Fn() -> i32>::new_stable(move || v + 1, |p| p as _)
+            .ok()
+            .unwrap()
+            .leak();
+    assert_eq!(f(), 1235);
+
+    // Zero-sized values don't allocate
+    let b = Value2w::<dyn std::fmt::Debug>::new_stable((), |p| p as _)
+        .unwrap()
+        .into_box();
+    assert_eq!(format!("{:?}", b), "()");
+}
+
+#[test]
+fn try_new_stable() {
+    use stack_dst::{CapacityError, PushError};
+    use std::fmt::Display;
+    type Pair = (Value2w<dyn Display>, Value2w<dyn Display>);
+    // A fallible builder, using `?` to propagate the error
+    fn build_pair(a: u64, b: u64) -> Result<Pair, CapacityError<u64>> {
+        let a = Value2w::try_new_stable(a, |p| p as _)?;
+        let b = Value2w::try_new_stable(b, |p| p as _)?;
+        Ok((a, b))
+    }
+    let (a, b) = build_pair(1, 2).unwrap();
+    assert_eq!(format!("{}{}", a, b), "12");
+
+    let e = Value2w::<dyn std::fmt::Debug>::try_new_stable([0usize; 2], |p| p as _)
+        .err()
+        .unwrap();
+    assert_eq!(e.kind(), PushError::TooLarge);
+    assert_eq!(e.into_inner(), [0, 0]);
+}
+
+#[test]
+fn new_cloned() {
+    use std::cell::Cell;
+    use std::fmt;
+    use std::rc::Rc;
+    // Counts the number of live clones
+    struct Counted(u32, Rc<Cell<usize>>);
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            self.1.set(self.1.get() + 1);
+            Counted(self.0, self.1.clone())
+        }
+    }
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.1.set(self.1.get() - 1);
+        }
+    }
+    impl fmt::Debug for Counted {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "Counted({})", self.0)
+        }
+    }
+    let live = Rc::new(Cell::new(1));
+    let orig = Counted(5, live.clone());
+
+    let v = Value8w::<dyn fmt::Debug>::new_cloned(&orig, |p| p as _).unwrap();
+    assert_eq!(format!("{:?}", v), "Counted(5)");
+    assert_eq!(live.get(), 2);
+    drop(v);
+    assert_eq!(live.get(), 1);
+
+    // Doesn't fit (two words of data, plus the vtable), so no clone is made
+    assert!(Value2w::<dyn fmt::Debug>::new_cloned(&orig, |p| p as _).is_err());
+    assert_eq!(live.get(), 1);
+    assert_eq!(orig.0, 5);
+}
+
+#[test]
+#[cfg(feature = "const_generics")]
+fn slice_as_chunks() {
+    let v = Value8w::<[u8]>::from_fn(10, |i| i as u8).unwrap();
+    let (chunks, rem) = v.as_chunks::<4>();
+    assert_eq!(chunks, &[[0, 1, 2, 3], [4, 5, 6, 7]]);
+    assert_eq!(rem, &[8, 9]);
+    let (chunks, rem) = v.as_chunks::<16>();
+    assert!(chunks.is_empty());
+    assert_eq!(rem.len(), 10);
+}
+
+#[test]
+fn typed_downcast() {
+    use std::fmt::Display;
+    #[derive(Debug, PartialEq)]
+    struct Celsius(i32);
+    impl Display for Celsius {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "{}C", self.0)
+        }
+    }
+    let mut val = Value8w::<dyn Display>::new_stable_typed(Celsius(21), |p| p as _)
+        .ok()
+        .unwrap();
+    assert_eq!(format!("{}", &*val), "21C");
+    assert!(val.is::<Celsius>());
+    assert_eq!(val.downcast_ref_typed::<i32>(), None);
+    val.downcast_mut_typed::<Celsius>().unwrap().0 += 1;
+    assert_eq!(val.downcast_ref_typed::<Celsius>(), Some(&Celsius(22)));
+    let val = val.downcast::<u8>().err().unwrap();
+    assert_eq!(val.downcast::<Celsius>().ok(), Some(Celsius(22)));
+}
+
+#[test]
+fn typed_as_any() {
+    use std::any::Any;
+    use std::fmt::Debug;
+    // `Debug` doesn't have `Any` as a supertrait
+    let mut val = Value8w::<dyn Debug>::new_stable_typed(vec![1u16, 2, 3], |p| p as _)
+        .ok()
+        .unwrap();
+    {
+        let any: &dyn Any = val.as_any();
+        assert!(any.downcast_ref::<u16>().is_none());
+        assert_eq!(any.downcast_ref::<Vec<u16>>(), Some(&vec![1, 2, 3]));
+    }
+    val.as_any_mut().downcast_mut::<Vec<u16>>().unwrap().push(4);
+    assert_eq!(format!("{:?}", &*val), "[1, 2, 3, 4]");
+    // The type ID is still intact
+    assert!(val.is::<Vec<u16>>());
+}
+
+#[test]
+fn typed_wide_words() {
+    use std::any::Any;
+    use std::fmt::Debug;
+    // Buffer words are larger than `usize`, so the type ID, upcast function and metadata are padded
+    type Buf = ::stack_dst::buffers::ConstArrayBuf<u128, 6>;
+    let mut val = stack_dst::Value::<dyn Debug, Buf>::new_stable_typed(0x1234u32, |p| p as _)
+        .ok()
+        .unwrap();
+    assert_eq!(format!("{:?}", &*val), "4660");
+    assert!(val.is::<u32>());
+    assert_eq!(val.stored_type_id(), std::any::TypeId::of::<u32>());
+    {
+        let any: &dyn Any = val.as_any();
+        assert_eq!(any.downcast_ref::<u32>(), Some(&0x1234));
+    }
+    *val.as_any_mut().downcast_mut::<u32>().unwrap() += 1;
+    assert_eq!(val.downcast_ref_typed::<u32>(), Some(&0x1235));
+
+    let val = stack_dst::Value::<[u16], Buf>::new_stable_typed([1u16, 2, 3], |p| p as _)
+        .ok()
+        .unwrap();
+    assert_eq!(&val[..], &[1, 2, 3]);
+    assert_eq!(val.as_any().downcast_ref::<[u16; 3]>(), Some(&[1, 2, 3]));
+}
+
+#[test]
+fn slice_binary_search() {
+    let v = Value8w::<[i32]>::new_stable([-5, 0, 2, 2, 7, 100], |p| p).unwrap();
+    assert_eq!(v.binary_search(&-5), Ok(0));
+    assert_eq!(v.binary_search(&7), Ok(4));
+    assert!(v.binary_search(&2) == Ok(2) || v.binary_search(&2) == Ok(3));
+    assert_eq!(v.binary_search(&-10), Err(0));
+    assert_eq!(v.binary_search(&50), Err(5));
+    assert_eq!(v.binary_search(&1000), Err(6));
+    assert_eq!(v.binary_search_by(|p| p.cmp(&100)), Ok(5));
+    assert_eq!(v.binary_search_by(|p| p.cmp(&1)), Err(2));
+}
+
+#[test]
+fn future_poll_to_completion() {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+    fn noop_raw() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            noop_raw()
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+    let waker = unsafe { Waker::from_raw(noop_raw()) };
+    let mut cx = Context::from_waker(&waker);
+
+    // A future that is pending a few times before completing
+    struct Countdown(u32);
+    impl Future for Countdown {
+        type Output = i32;
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<i32> {
+            if self.0 == 0 {
+                Poll::Ready(42)
+            } else {
+                self.0 -= 1;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+    let mut fut = Value8w::<dyn Future<Output = i32>>::from_future(Countdown(2))
+        .ok()
+        .unwrap();
+    // SAFE: `fut` is never moved after being pinned
+    let mut fut = unsafe { Pin::new_unchecked(&mut fut) };
+    assert_eq!(fut.as_mut().poll(&mut cx), Poll::Pending);
+    assert_eq!(fut.as_mut().poll(&mut cx), Poll::Pending);
+    assert_eq!(fut.as_mut().poll(&mut cx), Poll::Ready(42));
+
+    let mut n = 0;
+    let mut fut = Value8w::<dyn Future<Output = u32>>::from_poll_fn(move |_| {
+        n += 1;
+        if n == 2 {
+            Poll::Ready(n)
+        } else {
+            Poll::Pending
+        }
+    })
+    .ok()
+    .unwrap();
+    let mut fut = unsafe { Pin::new_unchecked(&mut fut) };
+    assert_eq!(fut.as_mut().poll(&mut cx), Poll::Pending);
+    assert_eq!(fut.as_mut().poll(&mut cx), Poll::Ready(2));
+}
+
+#[test]
+fn future_not_unpin() {
+    use std::future::Future;
+    use std::marker::PhantomPinned;
+    use std::pin::Pin;
+    use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+    fn noop_raw() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            noop_raw()
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+    let waker = unsafe { Waker::from_raw(noop_raw()) };
+    let mut cx = Context::from_waker(&waker);
+
+    // A `!Unpin` future that checks it hasn't moved between polls
+    struct AddressCheck {
+        addr: usize,
+        _pin: PhantomPinned,
+    }
+    impl Future for AddressCheck {
+        type Output = ();
+        fn poll(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<()> {
+            // SAFE: Nothing is moved out
+            let this = unsafe { self.get_unchecked_mut() };
+            let addr = this as *mut Self as usize;
+            if this.addr == 0 {
+                this.addr = addr;
+                Poll::Pending
+            } else {
+                assert_eq!(this.addr, addr, "Future moved while pinned");
+                Poll::Ready(())
+            }
+        }
+    }
+    let fut = Value8w::<dyn Future<Output = ()>>::from_future(AddressCheck {
+        addr: 0,
+        _pin: PhantomPinned,
+    })
+    .ok()
+    .unwrap();
+    // Moving the `Value` before pinning is fine
+    let mut fut = Box::pin(fut);
+    assert_eq!(fut.as_mut().poll(&mut cx), Poll::Pending);
+    assert_eq!(fut.as_mut().poll(&mut cx), Poll::Ready(()));
+}
+
+#[test]
+fn future_as_pin_mut() {
+    use std::future::Future;
+    use std::marker::PhantomPinned;
+    use std::pin::Pin;
+    use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+    fn noop_raw() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            noop_raw()
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+    let waker = unsafe { Waker::from_raw(noop_raw()) };
+    let mut cx = Context::from_waker(&waker);
+
+    // A `!Unpin` future that records its address on the first poll, and checks it on the second
+    struct Anchored {
+        addr: *const Anchored,
+        _pin: PhantomPinned,
+    }
+    impl Future for Anchored {
+        type Output = bool;
+        fn poll(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<bool> {
+            // SAFE: Nothing is moved out
+            let this = unsafe { self.get_unchecked_mut() };
+            if this.addr.is_null() {
+                this.addr = this;
+                Poll::Pending
+            } else {
+                Poll::Ready(std::ptr::eq(this.addr, this))
+            }
+        }
+    }
+    let mut fut = Value8w::<dyn Future<Output = bool>>::from_future(Anchored {
+        addr: std::ptr::null(),
+        _pin: PhantomPinned,
+    })
+    .ok()
+    .unwrap();
+    // SAFE: `fut` is never moved after being pinned (it's shadowed)
+    let mut fut = unsafe { Pin::new_unchecked(&mut fut) };
+    // Poll the contained future directly, through the pin projection
+    let inner: Pin<&mut dyn Future<Output = bool>> = fut.as_mut().as_pin_mut();
+    assert_eq!(inner.poll(&mut cx), Poll::Pending);
+    assert_eq!(fut.as_mut().as_pin_mut().poll(&mut cx), Poll::Ready(true));
+}
+
+#[test]
+fn hash_bytes() {
+    use std::any::Any;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::Hasher;
+    fn hash_of(v: &Value2w<dyn Any>) -> u64 {
+        let mut h = DefaultHasher::new();
+        // SAFE: Only used with integers (no padding)
+        unsafe { v.hash_bytes(&mut h) };
+        h.finish()
+    }
+    let a = Value2w::<dyn Any>::new_stable(0x1234_5678_u64, |p| p).unwrap();
+    let b = Value2w::<dyn Any>::new_stable(0x1234_5678_u64, |p| p).unwrap();
+    let c = Value2w::<dyn Any>::new_stable(0x1234_5679_u64, |p| p).unwrap();
+    assert_eq!(hash_of(&a), hash_of(&b));
+    assert_ne!(hash_of(&a), hash_of(&c));
+}
+
+#[test]
+fn payload_bytes_crc() {
+    // Bitwise CRC-32 (IEEE)
+    fn crc32(bytes: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for &b in bytes {
+            crc ^= b as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 != 0 {
+                    (crc >> 1) ^ 0xEDB8_8320
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+        !crc
+    }
+    let data = *b"123456789";
+    let v = Value8w::<[u8]>::new_stable(data, |p| p).unwrap();
+    // SAFE: `[u8]` has no padding
+    let crc = unsafe { v.with_payload_bytes(crc32) };
+    assert_eq!(crc, crc32(&data));
+    assert_eq!(crc, 0xCBF4_3926);
+    // Only the payload, not the length metadata
+    assert_eq!(unsafe { v.with_payload_bytes(|b| b.len()) }, data.len());
+}
+
+#[test]
+fn str_insert() {
+    let mut s = Value8w::<str>::new_str("cde").unwrap();
+    s.insert_str(0, "ab").unwrap();
+    assert_eq!(&s[..], "abcde");
+    s.insert_str(2, "ü").unwrap();
+    assert_eq!(&s[..], "abücde");
+    let l = s.len();
+    s.insert_str(l, "fg").unwrap();
+    assert_eq!(&s[..], "abücdefg");
+
+    // Overflow leaves the string unchanged
+    let mut s = Value2w::<str>::new_str("Hi").unwrap();
+    assert!(s.insert_str(1, &"x".repeat(16)).is_err());
+    assert_eq!(&s[..], "Hi");
+}
+
+#[test]
+#[should_panic]
+fn str_insert_boundary() {
+    let mut s = Value8w::<str>::new_str("Héllo").unwrap();
+    let _ = s.insert_str(2, "x");
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn in_buffer_recover() {
+    use stack_dst::Value;
+    use std::fmt::Debug;
+    use std::mem::MaybeUninit;
+
+    /// A `Vec` buffer that refuses to grow past a fixed size
+    struct CappedVec(Vec<MaybeUninit<usize>>, usize);
+    unsafe impl stack_dst::DataBuf for CappedVec {
+        type Inner = usize;
+        fn as_ref(&self) -> &[MaybeUninit<usize>] {
+            &self.0
+        }
+        fn as_mut(&mut self) -> &mut [MaybeUninit<usize>] {
+            &mut self.0
+        }
+        fn extend(&mut self, len: usize) -> Result<(), ()> {
+            if len > self.1 {
+                Err(())
+            } else {
+                stack_dst::DataBuf::extend(&mut self.0, len)
+            }
+        }
+    }
+
+    let buf = CappedVec(Vec::with_capacity(4), 4);
+    let alloc = buf.0.as_ptr();
+    let (buf, val) =
+        match Value::<dyn Debug, _>::in_buffer_stable(buf, [1usize, 2, 3, 4], |v| v as _) {
+            Ok(_) => panic!("Value should not fit"),
+            Err(e) => e,
+        };
+    assert_eq!(val, [1, 2, 3, 4]);
+    assert_eq!(buf.0.as_ptr(), alloc);
+
+    // Retry with a smaller value, reusing the same allocation
+    let v = Value::<dyn Debug, _>::in_buffer_stable(buf, [1usize, 2], |v| v as _)
+        .ok()
+        .expect("Value should fit");
+    assert_eq!(format!("{:?}", v), "[1, 2]");
+}
+
+#[test]
+fn slice_reserve_fixed() {
+    // 8 words, one of which holds the length
+    let mut v = Value8w::<[usize]>::empty_slice().unwrap();
+    assert_eq!(v.capacity(), 7);
+    v.reserve(7).unwrap();
+    assert!(v.reserve(8).is_err());
+    v.extend(0..7).ok().unwrap();
+    assert_eq!(v.capacity(), 7);
+    assert!(v.reserve(1).is_err());
+    assert!(v.reserve(usize::MAX).is_err());
+    assert_eq!(&v[..], &[0, 1, 2, 3, 4, 5, 6]);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn slice_reserve_vec() {
+    let mut v = stack_dst::Value::<[u32], ::stack_dst::buffers::PtrVec>::empty_slice().unwrap();
+    v.extend(0..3).ok().unwrap();
+    v.reserve(100).unwrap();
+    let cap = v.capacity();
+    assert!(cap >= 103);
+    // Existing items (and the length) are preserved
+    assert_eq!(&v[..], &[0, 1, 2]);
+    v.extend(3..103).ok().unwrap();
+    assert_eq!(v.capacity(), cap);
+    assert_eq!(v.len(), 103);
+    assert_eq!(v[102], 102);
+}
+
+#[test]
+fn slice_drain() {
+    type Buf = ::stack_dst::buffers::Ptr16;
+    let strings = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    let make = || {
+        stack_dst::Value::<[String], Buf>::empty_slice()
+            .unwrap()
+            .extended(strings(&["a", "b", "c", "d", "e"]).into_iter())
+            .ok()
+            .unwrap()
+    };
+
+    // Fully consumed
+    let mut v = make();
+    assert_eq!(v.drain(1..3).collect::<Vec<_>>(), strings(&["b", "c"]));
+    assert_eq!(&v[..], &strings(&["a", "d", "e"])[..]);
+
+    // Consumed from both ends, then dropped early
+    let mut v = make();
+    {
+        let mut it = v.drain(1..=3);
+        assert_eq!(it.len(), 3);
+        assert_eq!(it.next_back(), Some("d".to_string()));
+        assert_eq!(it.next(), Some("b".to_string()));
+    }
+    assert_eq!(&v[..], &strings(&["a", "e"])[..]);
+
+    // Unbounded ranges
+    let mut v = make();
+    assert_eq!(v.drain(3..).count(), 2);
+    assert_eq!(v.drain(..).count(), 3);
+    assert!(v.is_empty());
+    // Capacity is retained
+    v.append("z".to_string()).unwrap();
+    assert_eq!(&v[..], &strings(&["z"])[..]);
+}
+
+#[test]
+fn slice_drain_panic() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::rc::Rc;
+
+    struct PanicOnDrop {
+        _counter: Rc<()>,
+        panic: bool,
+    }
+    impl Drop for PanicOnDrop {
+        fn drop(&mut self) {
+            if self.panic {
+                panic!("PanicOnDrop");
+            }
+        }
+    }
+
+    let counter = Rc::new(());
+    let mut v = stack_dst::Value::<[PanicOnDrop], ::stack_dst::buffers::Ptr16>::empty_slice()
+        .unwrap()
+        .extended(
+            [false, true, false, false]
+                .iter()
+                .map(|&panic| PanicOnDrop {
+                    _counter: counter.clone(),
+                    panic,
+                }),
+        )
+        .ok()
+        .unwrap();
+    let r = catch_unwind(AssertUnwindSafe(|| {
+        let _ = v.drain(1..3);
+    }));
+    assert!(r.is_err());
+    // The tail is still moved down, and only the items that weren't dropped remain
+    assert_eq!(v.len(), 2);
+    assert!(v.iter().all(|v| !v.panic));
+    assert_eq!(Rc::strong_count(&counter), 3);
+    drop(v);
+    assert_eq!(Rc::strong_count(&counter), 1);
+}
+
+#[test]
+fn stored_bytes_round_trip() {
+    let v = Value8w::<[u8]>::new_stable([1, 2, 3, 4, 5], |v| v).unwrap();
+    let (bytes, meta, meta_len) = unsafe { v.as_stored_bytes() };
+    assert_eq!(bytes, &[1, 2, 3, 4, 5]);
+    assert_eq!(&meta[..meta_len], &[5]);
+
+    // Simulate sending the data over a wire
+    let wire_bytes = bytes.to_vec();
+    let wire_meta = meta[..meta_len].to_vec();
+    let v2 = unsafe { Value8w::<[u8]>::from_stored_bytes(&wire_bytes, &wire_meta) }.unwrap();
+    assert_eq!(&v2[..], &v[..]);
+
+    // Too large for the buffer
+    let big = [0u8; 128];
+    assert!(unsafe { Value8w::<[u8]>::from_stored_bytes(&big, &[128]) }.is_err());
+    // Wrong metadata length
+    assert!(unsafe { Value8w::<[u8]>::from_stored_bytes(&wire_bytes, &[]) }.is_err());
+}
+
+#[test]
+#[cfg(feature = "core_error")]
+fn error_sources() {
+    use std::error::Error;
+    use std::fmt;
+
+    #[derive(Debug)]
+    struct Wrapper(&'static str, Option<Box<dyn Error + 'static>>);
+    impl fmt::Display for Wrapper {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str(self.0)
+        }
+    }
+    impl Error for Wrapper {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            self.1.as_ref().map(|e| &**e as _)
+        }
+    }
+
+    let inner = Wrapper("inner", None);
+    let middle = Wrapper("middle", Some(Box::new(inner)));
+    let outer = Wrapper("outer", Some(Box::new(middle)));
+    let e = stack_dst::Value::<dyn Error, ::stack_dst::buffers::Ptr8>::from_error(outer).unwrap();
+    assert_eq!(e.to_string(), "outer");
+    let chain: Vec<_> = e.sources().map(|e| e.to_string()).collect();
+    assert_eq!(chain, ["middle", "inner"]);
+}
+
+#[test]
+fn slice_concat() {
+    let header = [0xAAu8, 0x55];
+    let body = b"Hello";
+    let footer = [0u8; 3];
+    let v = Value8w::<[u8]>::concat(&[&header, body, &footer]).unwrap();
+    assert_eq!(&v[..], b"\xAA\x55Hello\0\0\0");
+
+    let v = Value8w::<[u8]>::concat(&[]).unwrap();
+    assert!(v.is_empty());
+
+    // Doesn't fit in the buffer
+    let big = [0u8; 64];
+    assert!(Value8w::<[u8]>::concat(&[&header, &big]).is_err());
+}
+
+#[test]
+fn ptr_eq() {
+    use std::fmt::Display;
+
+    let a = Value8w::<dyn Display>::new_stable(1234, |v| v as _).unwrap();
+    let b = Value8w::<dyn Display>::new_stable(1234, |v| v as _).unwrap();
+    let refs = [&a, &b, &a];
+    assert!(refs[0].ptr_eq(refs[2]));
+    assert!(!refs[0].ptr_eq(refs[1]));
+
+    // The data pointer is that of the contained value (and moves with the `Value`)
+    assert_eq!(a.data_ptr(), &*a as *const dyn Display as *const ());
+    let a_ptr = a.data_ptr();
+    let moved = Box::new(a);
+    assert_ne!(moved.data_ptr(), a_ptr);
+    assert_eq!(
+        moved.data_ptr(),
+        &**moved as *const dyn Display as *const ()
+    );
+}
+
+#[test]
+fn new_from_ref_runtime_coercion() {
+    use std::fmt::Display;
+    use std::mem::ManuallyDrop;
+
+    struct Shout(String);
+    impl Display for Shout {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "{}!", self.0.to_uppercase())
+        }
+    }
+
+    fn make(shout: bool) -> Value8w<dyn Display> {
+        type Pair = (ManuallyDrop<String>, ManuallyDrop<Shout>);
+        let pair: Pair = (
+            ManuallyDrop::new("hello".to_owned()),
+            ManuallyDrop::new(Shout("hello".to_owned())),
+        );
+        // Pick the coercion (and so the stored type) at runtime
+        let coercions: [fn(&Pair) -> &(dyn Display + 'static); 2] = [|p| &*p.0, |p| &*p.1];
+        let r = coercions[shout as usize](&pair);
+        let rv = unsafe { Value8w::<dyn Display>::new_from_ref(r) }
+            .ok()
+            .unwrap();
+        // Only drop the one that wasn't moved into the value
+        let (s, shouting) = pair;
+        if shout {
+            drop(ManuallyDrop::into_inner(s));
+        } else {
+            drop(ManuallyDrop::into_inner(shouting));
+        }
+        rv
+    }
+    assert_eq!(make(false).to_string(), "hello");
+    assert_eq!(make(true).to_string(), "HELLO!");
+
+    // Too large for the buffer
+    let big = [0u8; 128];
+    assert!(unsafe { Value8w::<[u8]>::new_from_ref(&big[..]) }.is_err());
+}
+
+#[test]
+fn slice_uninit_reverse() {
+    use std::mem::MaybeUninit;
+
+    type Buf = ::stack_dst::buffers::Ptr16;
+    let mut v = stack_dst::value::ValueUninit::<[String], Buf>::new(4).unwrap();
+    assert_eq!(v.len(), 4);
+    for i in (0..4).rev() {
+        v.as_mut_slice()[i] = MaybeUninit::new(format!("item{}", i));
+    }
+    let v = unsafe { v.assume_init() };
+    assert_eq!(&v[..], &["item0", "item1", "item2", "item3"]);
+
+    // Doesn't fit
+    assert!(stack_dst::value::ValueUninit::<[String], Buf>::new(8).is_err());
+    // Size overflow (for a byte buffer the word count would wrap)
+    assert!(
+        stack_dst::value::ValueUninit::<[u8], ::stack_dst::buffers::U8_32>::new(usize::MAX)
+            .is_err()
+    );
+}
+
+#[test]
+fn str_repeat() {
+    let s = Value8w::<str>::repeat("ab", 3).unwrap();
+    assert_eq!(&s[..], "ababab");
+    let s = Value8w::<str>::repeat("ab", 0).unwrap();
+    assert_eq!(&s[..], "");
+
+    // Too large for the buffer
+    assert!(Value8w::<str>::repeat("ab", 64).is_err());
+    // Length overflow
+    assert!(Value8w::<str>::repeat("ab", usize::MAX).is_err());
+    // Length fits in `usize`, but the word count (including the metadata) doesn't
+    assert!(Value8w::<str>::repeat("a", usize::MAX).is_err());
+}
+
+#[test]
+fn str_trim_in_place() {
+    let mut s = Value8w::<str>::new_str("  hi  ").unwrap();
+    s.trim_in_place();
+    assert_eq!(&s[..], "hi");
+    assert_eq!(s.len(), 2);
+
+    let mut s = Value8w::<str>::new_str("\t\u{a0}héllo \n").unwrap();
+    s.trim_start_in_place();
+    assert_eq!(&s[..], "héllo \n");
+    s.trim_end_in_place();
+    assert_eq!(&s[..], "héllo");
+    // Still usable after the move
+    s.append_str("!").unwrap();
+    assert_eq!(&s[..], "héllo!");
+
+    let mut s = Value8w::<str>::new_str("   ").unwrap();
+    s.trim_in_place();
+    assert_eq!(&s[..], "");
+}
+
+#[test]
+fn str_replace_range() {
+    let mut s = Value8w::<str>::new_str("one two three").unwrap();
+    // Shorter
+    s.replace_range(4..7, "2").unwrap();
+    assert_eq!(&s[..], "one 2 three");
+    // Longer
+    s.replace_range(4..=4, "deux/zwei").unwrap();
+    assert_eq!(&s[..], "one deux/zwei three");
+    s.replace_range(.., "").unwrap();
+    assert_eq!(&s[..], "");
+
+    // Overflow leaves the string as-is
+    let mut s = Value8w::<str>::new_str("abc").unwrap();
+    let big = "x".repeat(8 * std::mem::size_of::<usize>());
+    assert!(s.replace_range(1..2, &big).is_err());
+    assert_eq!(&s[..], "abc");
+}
+
+#[test]
+#[should_panic]
+fn str_replace_range_boundary() {
+    let mut s = Value8w::<str>::new_str("héllo").unwrap();
+    let _ = s.replace_range(1..2, "e");
+}
+
+#[test]
+fn str_capacity_bytes() {
+    let word = std::mem::size_of::<usize>();
+    let mut s = Value8w::<str>::new_str("héllo").unwrap();
+    assert_eq!(s.len_bytes(), 6);
+    // 8 words, one of which holds the length
+    assert_eq!(s.capacity_bytes(), 7 * word);
+
+    // Fill exactly to capacity, then check that one more byte is rejected
+    let fill = "x".repeat(s.capacity_bytes() - s.len_bytes());
+    s.append_str(&fill).unwrap();
+    assert_eq!(s.len_bytes(), s.capacity_bytes());
+    assert!(s.append_str("y").is_err());
+}
+
+#[test]
+#[cfg(not(feature = "typenum_buffers"))]
+fn const_generic_aliases() {
+    use stack_dst::buffers::{ConstArrayBuf, Ptr8};
+
+    // Without `typenum_buffers`, the aliases are the const-generic buffers
+    let buf: ConstArrayBuf<usize, 8> = Ptr8::default();
+    let v = stack_dst::Value::<[usize], _>::in_buffer_stable(buf, [1, 2, 3], |p| p)
+        .ok()
+        .unwrap();
+    assert_eq!(&v[..], &[1, 2, 3]);
+}
+
+#[test]
+fn array_buffer_sizes() {
+    use stack_dst::buffers::Array;
+
+    // 5 words: 4 for data and 1 for the length
+    let v = stack_dst::Value::<[usize], _>::in_buffer_stable(
+        Array::<usize, 5>::new(),
+        [1, 2, 3, 4],
+        |p| p,
+    )
+    .ok()
+    .unwrap();
+    assert_eq!(&v[..], &[1, 2, 3, 4]);
+    assert!(stack_dst::Value::<[usize], _>::in_buffer_stable(
+        Array::<usize, 5>::new(),
+        [1, 2, 3, 4, 5],
+        |p| p
+    )
+    .is_err());
+
+    let mut stack = stack_dst::Stack::<str, Array<u8, 17>>::new();
+    stack.push_str("Hello").unwrap();
+    assert_eq!(stack.top(), Some("Hello"));
+
+    let mut fifo = stack_dst::Fifo::<[u32], Array<usize, 100>>::new();
+    for i in 0..20 {
+        fifo.push_copied(&[i, i + 1]).unwrap();
+    }
+    assert_eq!(fifo.len(), 20);
+    assert_eq!(fifo.front(), Some(&[0, 1][..]));
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn try_clone_overflow() {
+    use std::mem::MaybeUninit;
+    // A `Vec` buffer with a size limit, that defaults to a small limit
+    struct LimitedVec(Vec<MaybeUninit<usize>>, usize);
+    impl Default for LimitedVec {
+        fn default() -> Self {
+            LimitedVec(Vec::new(), 3)
+        }
+    }
+    unsafe impl stack_dst::DataBuf for LimitedVec {
+        type Inner = usize;
+        fn as_ref(&self) -> &[MaybeUninit<usize>] {
+            &self.0
+        }
+        fn as_mut(&mut self) -> &mut [MaybeUninit<usize>] {
+            &mut self.0
+        }
+        fn extend(&mut self, len: usize) -> Result<(), ()> {
+            if len > self.1 {
+                Err(())
+            } else {
+                stack_dst::DataBuf::extend(&mut self.0, len)
+            }
+        }
+    }
+
+    // Fits in the default buffer
+    let v = stack_dst::Value::<[usize], LimitedVec>::from_fn(2, |i| i).unwrap();
+    assert_eq!(&v.try_clone().unwrap()[..], &[0, 1]);
+
+    // Source was given a larger buffer, so the clone doesn't fit
+    let v = stack_dst::Value::<[usize], _>::empty_slice_with_buffer(LimitedVec(Vec::new(), 16))
+        .unwrap()
+        .extended(0..8)
+        .ok()
+        .unwrap();
+    assert!(v.try_clone().is_err());
+
+    let s = stack_dst::Value::<str, _>::new_str_in_buffer(
+        LimitedVec(Vec::new(), 16),
+        "This is too long for the default",
+    )
+    .unwrap();
+    assert!(s.try_clone().is_err());
+    let s = stack_dst::Value::<str, LimitedVec>::new_str("Short").unwrap();
+    assert_eq!(&s.try_clone().unwrap()[..], "Short");
+}
+
+#[test]
+fn slice_spare_capacity() {
+    use std::mem::MaybeUninit;
+    // 8 words, one of which holds the length
+    let mut v = Value8w::<[u16]>::new_stable([1, 2], |p| p).unwrap();
+    let per_word = std::mem::size_of::<usize>() / 2;
+    assert_eq!(v.spare_capacity_mut().len(), 7 * per_word - 2);
+    for (i, slot) in v.spare_capacity_mut().iter_mut().enumerate() {
+        *slot = MaybeUninit::new(i as u16 + 3);
+    }
+    unsafe { v.set_len(7 * per_word) };
+    assert_eq!(v[..4], [1, 2, 3, 4]);
+    assert_eq!(*v.last().unwrap(), 7 * per_word as u16);
+    assert!(v.spare_capacity_mut().is_empty());
+
+    // Growable buffers expose space after a `reserve`
+    #[cfg(feature = "alloc")]
+    {
+        let mut v = stack_dst::Value::<[u64], stack_dst::buffers::PtrVec>::empty_slice().unwrap();
+        v.reserve(10).unwrap();
+        assert!(v.spare_capacity_mut().len() >= 10);
+        for slot in &mut v.spare_capacity_mut()[..10] {
+            *slot = MaybeUninit::new(5);
+        }
+        unsafe { v.set_len(10) };
+        assert_eq!(&v[..], &[5; 10]);
+    }
+}
+
+#[test]
+fn str_from_fmt() {
+    let s = Value8w::<str>::from_fmt(format_args!("{}-{}", 1, 2)).unwrap();
+    assert_eq!(&s[..], "1-2");
+    let s = Value8w::<str>::from_fmt(format_args!("{:>8}|{:<4}|", "ab", 'c')).unwrap();
+    assert_eq!(&s[..], "      ab|c   |");
+    // Too long for the buffer
+    assert!(Value2w::<str>::from_fmt(format_args!("{}", "x".repeat(64))).is_err());
+}
+
+#[test]
+fn with_mut() {
+    let mut count = 0usize;
+    {
+        let mut v = Value8w::<dyn FnMut() -> usize>::new_stable(
+            || {
+                count += 1;
+                count
+            },
+            |p| p as _,
+        )
+        .ok()
+        .unwrap();
+        assert_eq!(v.with_mut(|f| f()), 1);
+        assert_eq!(v.with_mut(|f| f() + f()), 5);
+    }
+    assert_eq!(count, 3);
+}
+
+#[test]
+fn for_trait_object_sizes() {
+    use stack_dst::buffers::{for_trait_object, TraitObject};
+    use std::fmt::Debug;
+    assert_eq!(for_trait_object::<()>(), 1);
+    assert_eq!(for_trait_object::<u8>(), 2);
+    assert_eq!(for_trait_object::<usize>(), 2);
+    assert_eq!(for_trait_object::<[usize; 3]>(), 4);
+    assert_eq!(for_trait_object::<(usize, u8)>(), 3);
+    assert_eq!(for_trait_object::<String>(), 4);
+
+    // Anything up to a word fits in a `TraitObject` buffer
+    assert!(stack_dst::Value::<dyn Debug, TraitObject>::new_stable(1usize, |p| p as _).is_ok());
+    assert!(
+        stack_dst::Value::<dyn Debug, TraitObject>::new_stable([1usize; 2], |p| p as _).is_err()
+    );
+    // The recommended size is exact
+    const N: usize = for_trait_object::<String>();
+    let v = stack_dst::Value::<dyn Debug, [std::mem::MaybeUninit<usize>; N]>::in_buffer_stable(
+        [std::mem::MaybeUninit::uninit(); N],
+        String::from("Hi"),
+        |p| p as _,
+    );
+    assert!(v.is_ok());
+}
+
+#[test]
+fn slice_rotate() {
+    let mut v = Value8w::<[i32]>::new_stable([1, 2, 3, 4, 5], |p| p).unwrap();
+    v.rotate_left(2);
+    assert_eq!(&v[..], &[3, 4, 5, 1, 2]);
+    v.rotate_right(3);
+    assert_eq!(&v[..], &[5, 1, 2, 3, 4]);
+    v.rotate_left(0);
+    v.rotate_right(5);
+    assert_eq!(&v[..], &[5, 1, 2, 3, 4]);
+}
+
+#[test]
+fn bytes_as_pod() {
+    // Use a `u32` buffer, so the start of the data is aligned for a `u32`
+    type Buf = ::stack_dst::buffers::FromSlice<[std::mem::MaybeUninit<u32>; 4], u32>;
+    let header = 0xCAFE_0001u32.to_ne_bytes();
+    let buf = unsafe { Buf::new([std::mem::MaybeUninit::uninit(); 4]) };
+    let mut v = stack_dst::Value::<[u8], _>::empty_slice_with_buffer(buf).unwrap();
+    for &b in header.iter().chain(&[0xAA, 0xBB]) {
+        v.append(b).unwrap();
+    }
+    assert_eq!(v.as_pod::<u32>(), Some(&0xCAFE_0001));
+    assert_eq!(
+        v.as_pod::<u16>().copied(),
+        Some(u16::from_ne_bytes([header[0], header[1]]))
+    );
+    // Too short
+    assert_eq!(v.as_pod::<u64>(), None);
+    v.shrink_to(3);
+    assert_eq!(v.as_pod::<u32>(), None);
+}
+
+#[test]
+fn slice_split_views() {
+    let mut v = Value8w::<[u8]>::new_stable([1, 2, 3, 4, 5], |p| p).unwrap();
+    let (a, b) = v.split_at(2);
+    assert_eq!(a, &[1, 2]);
+    assert_eq!(b, &[3, 4, 5]);
+    assert_eq!(v.split_at(5).1, &[]);
+    {
+        let (a, b) = v.split_at_mut(1);
+        a[0] = 10;
+        b[0] = 20;
+    }
+    assert_eq!(&v[..], &[10, 20, 3, 4, 5]);
+
+    let chunks: Vec<&[u8]> = v.chunks(2).collect();
+    assert_eq!(chunks, [&[10, 20][..], &[3, 4], &[5]]);
+    let sums: Vec<u8> = v.windows(2).map(|w| w[0] + w[1]).collect();
+    assert_eq!(sums, [30, 23, 7, 9]);
+    assert_eq!(v.windows(6).count(), 0);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn str_or_boxed() {
+    use stack_dst::value::InlineOrBoxed;
+
+    let s = Value2w::<str>::new_str_or_boxed("Short");
+    match s {
+        InlineOrBoxed::Inline(ref v) => assert_eq!(&v[..], "Short"),
+        InlineOrBoxed::Boxed(_) => panic!("Should be inline"),
+    }
+    assert_eq!(&*s, "Short");
+
+    let mut s = Value2w::<str>::new_str_or_boxed("This is longer than two words");
+    match s {
+        InlineOrBoxed::Inline(_) => panic!("Should be boxed"),
+        InlineOrBoxed::Boxed(ref v) => assert_eq!(&v[..], "This is longer than two words"),
+    }
+    s.make_ascii_uppercase();
+    assert_eq!(&*s, "THIS IS LONGER THAN TWO WORDS");
+}
+
+#[test]
+fn footprint() {
+    use std::any::Any;
+    let word = std::mem::size_of::<usize>();
+    assert_eq!(Value2w::<dyn Any>::FOOTPRINT, 2 * word);
+    assert_eq!(Value8w::<str>::FOOTPRINT, 8 * word);
+    assert_eq!(
+        stack_dst::Value::<[u8], stack_dst::buffers::U8_32>::FOOTPRINT,
+        32
+    );
+    // Stacks and queues add their offsets and item count
+    assert_eq!(
+        stack_dst::Stack::<dyn Any, stack_dst::buffers::Ptr8>::FOOTPRINT,
+        10 * word
+    );
+    assert_eq!(
+        stack_dst::Fifo::<dyn Any, stack_dst::buffers::Ptr8>::FOOTPRINT,
+        11 * word
+    );
+}
+
+#[test]
+fn slice_eq() {
+    let v = Value8w::<[i32]>::new_stable([1, 2, 3], |p| p).unwrap();
+    let same: &[i32] = &[1, 2, 3];
+    let other: &[i32] = &[1, 2, 4];
+    let empty: &[i32] = &[];
+    assert!(v == *same);
+    assert!(v == same);
+    assert!(v != other);
+    assert!(v != empty);
+    #[cfg(feature = "const_generics")]
+    {
+        assert_eq!(v, [1, 2, 3]);
+        assert_ne!(v, [1, 2, 3, 4]);
+    }
+    let e = Value8w::<[i32]>::empty_slice().unwrap();
+    assert!(e == empty);
+}
+
+#[test]
+fn slice_insert_sorted() {
+    let mut v = Value8w::<[i32]>::empty_slice().unwrap();
+    for &x in &[5, 1, 4, 1, 9, 2] {
+        v.insert_sorted(x).unwrap();
+    }
+    assert_eq!(&v[..], &[1, 1, 2, 4, 5, 9]);
+    assert_eq!(v.insert_sorted(3), Ok(3));
+    assert_eq!(v.insert_sorted(100), Ok(7));
+    assert_eq!(v.insert_sorted(-1), Ok(0));
+    assert_eq!(&v[..], &[-1, 1, 1, 2, 3, 4, 5, 9, 100]);
+
+    // Full (7 words, each holding two or more `i32`s)
+    let cap = v.capacity();
+    while v.len() < cap {
+        v.insert_sorted(0).unwrap();
+    }
+    assert_eq!(v.insert_sorted(0), Err(0));
+    assert!(v.windows(2).all(|w| w[0] <= w[1]));
+}
+
+#[test]
+fn slice_shrink_to() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    struct Tracked(u32, Rc<Cell<usize>>);
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.1.set(self.1.get() + 1);
+        }
+    }
+    let drops = Rc::new(Cell::new(0));
+    let mut v = Value8w::<[Tracked]>::empty_slice().unwrap();
+    for i in 0..3 {
+        v.append(Tracked(i, drops.clone())).ok().unwrap();
+    }
+
+    assert_eq!(v.shrink_to(3), 0);
+    assert_eq!(drops.get(), 0);
+    assert_eq!(v.shrink_to(1), 2);
+    assert_eq!(drops.get(), 2);
+    assert_eq!(v.len(), 1);
+    assert_eq!(v[0].0, 0);
+    assert_eq!(v.shrink_to(0), 1);
+    assert_eq!(drops.get(), 3);
+
+    drop(v);
+    assert_eq!(drops.get(), 3);
+}
+
+#[test]
+#[cfg(feature = "try_reserve")]
+fn slice_try_reserve() {
+    let mut v = ::stack_dst::Value::<[u16], ::stack_dst::buffers::PtrVec>::empty_slice().unwrap();
+    v.extend([1, 2, 3].iter().cloned()).ok().unwrap();
+    v.try_reserve(50).unwrap();
+    assert!(v.capacity() >= 53);
+    assert_eq!(&v[..], &[1, 2, 3]);
+    // Already have space, so nothing changes
+    let cap = v.capacity();
+    v.try_reserve(10).unwrap();
+    assert_eq!(v.capacity(), cap);
+
+    // The failure path (the size overflows, so `Vec` rejects it without trying to allocate)
+    assert!(v.try_reserve(usize::MAX - 1).is_err());
+    assert!(v.try_reserve(isize::MAX as usize / 2).is_err());
+    assert_eq!(v.capacity(), cap);
+    assert_eq!(&v[..], &[1, 2, 3]);
+
+    // With byte-sized words, the item count fits but adding the length slot overflows
+    let mut v = ::stack_dst::Value::<[u8], ::stack_dst::buffers::U8Vec>::empty_slice().unwrap();
+    v.extend([1, 2, 3].iter().cloned()).ok().unwrap();
+    assert!(v.try_reserve(usize::MAX - 3).is_err());
+    assert_eq!(&v[..], &[1, 2, 3]);
+}
+
+#[test]
+#[cfg(feature = "const_generics")]
+fn slice_as_array() {
+    let v = Value8w::<[u8]>::new_stable([0xAB, 0xCD, 0x01, 0x02], |p| p).unwrap();
+    let header: &[u8; 4] = v.as_array().unwrap();
+    assert_eq!(u16::from_be_bytes([header[0], header[1]]), 0xABCD);
+    assert!(v.as_array::<2>().is_none());
+    assert!(v.as_array::<5>().is_none());
+
+    let empty = Value8w::<[u8]>::empty_slice().unwrap();
+    assert_eq!(empty.as_array::<0>(), Some(&[]));
+}
+
+#[test]
+fn new_stable_mut() {
+    use std::fmt::Debug;
+    trait Counter: Debug {
+        fn bump(&mut self) -> u32;
+    }
+    #[derive(Debug)]
+    struct Count(u32);
+    impl Counter for Count {
+        fn bump(&mut self) -> u32 {
+            self.0 += 1;
+            self.0
+        }
+    }
+    // An accessor that only provides a `&mut` coercion
+    fn as_counter(v: &mut Count) -> &mut (dyn Counter + 'static) {
+        v
+    }
+    let mut v = Value8w::<dyn Counter>::new_stable_mut(Count(1), as_counter).unwrap();
+    assert_eq!(v.bump(), 2);
+    assert_eq!(format!("{:?}", v), "Count(2)");
+
+    // Doesn't fit
+    fn as_debug(v: &mut [usize; 8]) -> &mut (dyn Debug + 'static) {
+        v
+    }
+    assert_eq!(
+        Value8w::<dyn Debug>::new_stable_mut([1; 8], as_debug).err(),
+        Some([1; 8])
+    );
+}
+
+#[test]
+fn from_slice_buffer() {
+    use stack_dst::buffers::FromSlice;
+    use stack_dst::Value;
+    use std::mem::MaybeUninit;
+
+    // A minimal inline vector, only exposing its full backing storage
+    struct InlineVec {
+        len: usize,
+        items: [MaybeUninit<u32>; 6],
+    }
+    impl AsRef<[MaybeUninit<u32>]> for InlineVec {
+        fn as_ref(&self) -> &[MaybeUninit<u32>] {
+            &self.items
+        }
+    }
+    impl AsMut<[MaybeUninit<u32>]> for InlineVec {
+        fn as_mut(&mut self) -> &mut [MaybeUninit<u32>] {
+            &mut self.items
+        }
+    }
+    let storage = || InlineVec {
+        len: 0,
+        items: [MaybeUninit::uninit(); 6],
+    };
+
+    // SAFE: `as_ref` and `as_mut` both return the inline array
+    let buf = unsafe { FromSlice::new(storage()) };
+    let v = Value::<[u32], _>::in_buffer_stable(buf, [1, 2, 3], |p| p)
+        .ok()
+        .unwrap();
+    assert_eq!(&v[..], &[1, 2, 3]);
+
+    // Too large for the six `u32`s (with the length taking one or two of them)
+    let buf = unsafe { FromSlice::new(storage()) };
+    assert!(Value::<[u32], _>::in_buffer_stable(buf, [0u32; 6], |p| p).is_err());
+
+    let buf = unsafe { FromSlice::new(storage()) };
+    let mut fifo = stack_dst::Fifo::<[u32], _>::with_buffer(buf);
+    fifo.push_copied(&[4, 5]).unwrap();
+    assert_eq!(fifo.front(), Some(&[4, 5][..]));
+    let buf = unsafe { FromSlice::new(storage()) };
+    assert_eq!(buf.into_inner().len, 0);
+}
+
+#[test]
+fn index_forward() {
+    use std::ops::{Index, IndexMut};
+    #[derive(Copy, Clone)]
+    enum Channel {
+        Red,
+        Green,
+        Blue,
+    }
+    trait Palette: IndexMut<Channel, Output = u8> {}
+    struct Rgb([u8; 3]);
+    impl Index<Channel> for Rgb {
+        type Output = u8;
+        fn index(&self, c: Channel) -> &u8 {
+            &self.0[c as usize]
+        }
+    }
+    impl IndexMut<Channel> for Rgb {
+        fn index_mut(&mut self, c: Channel) -> &mut u8 {
+            &mut self.0[c as usize]
+        }
+    }
+    impl Palette for Rgb {}
+
+    let mut v = Value8w::<dyn Palette>::new_stable(Rgb([1, 2, 3]), |p| p as _)
+        .ok()
+        .unwrap();
+    assert_eq!(v[Channel::Green], 2);
+    v[Channel::Blue] = 30;
+    v[Channel::Red] += 10;
+    assert_eq!(
+        [v[Channel::Red], v[Channel::Green], v[Channel::Blue]],
+        [11, 2, 30]
+    );
+
+    // Slice and string indexing still work
+    let mut s = Value8w::<[u8]>::new_stable([1, 2, 3], |p| p).unwrap();
+    s[0] = 5;
+    assert_eq!(&s[..2], &[5, 2]);
+    let t = Value8w::<str>::new_str("Hello").unwrap();
+    assert_eq!(&t[1..3], "el");
+}